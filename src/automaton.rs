@@ -0,0 +1,224 @@
+use std::hash::Hash;
+
+use super::cycle::{self, Cycle};
+use super::direction::Direction;
+use super::grid::Grid;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Neighbourhood {
+    // The four orthogonal neighbours
+    VonNeumann,
+    // All eight surrounding cells
+    Moore,
+}
+
+impl Neighbourhood {
+    fn offsets(&self) -> &'static [(isize, isize)] {
+        match *self {
+            Neighbourhood::VonNeumann => &[(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Neighbourhood::Moore => &[
+                (-1, -1), (-1, 0), (-1, 1),
+                (0, -1), (0, 1),
+                (1, -1), (1, 0), (1, 1),
+            ],
+        }
+    }
+
+    // Neighbouring (row, col) positions that fall inside a grid of the given size
+    pub fn positions(&self, (row, col): (usize, usize), (rows, cols): (usize, usize))
+        -> impl Iterator<Item = (usize, usize)>
+    {
+        self.offsets().iter().filter_map(move |&(dr, dc)| {
+            let row = row.checked_add_signed(dr).filter(|&r| r < rows)?;
+            let col = col.checked_add_signed(dc).filter(|&c| c < cols)?;
+            Some((row, col))
+        })
+    }
+}
+
+/*
+ * Computes the next generation of a grid. The rule receives each cell's
+ * position, its current value and the values of its in-bounds neighbours.
+ */
+pub fn next_generation<T, F>(grid: &Grid<T>, neighbourhood: Neighbourhood, mut rule: F) -> Grid<T>
+where
+    F: FnMut((usize, usize), &T, &[&T]) -> T,
+{
+    let mut neighbours = Vec::with_capacity(8);
+    grid.map(|p, cell| {
+        neighbours.clear();
+        neighbours.extend(neighbourhood.positions(p, grid.size()).map(|n| &grid[n]));
+        rule(p, cell, &neighbours)
+    })
+}
+
+/*
+ * Slides every mobile cell as far as it can go towards `dir`, stopping at the
+ * edge, another mobile cell or any cell that is neither mobile nor empty.
+ * Returns how many cells moved.
+ */
+pub fn tilt<T, M, E>(grid: &mut Grid<T>, dir: Direction, is_mobile: M, is_empty: E) -> usize
+where
+    M: Fn(&T) -> bool,
+    E: Fn(&T) -> bool,
+{
+    let (rows, cols) = grid.size();
+    let (lanes, len) = match dir {
+        Direction::North | Direction::South => (cols, rows),
+        Direction::East | Direction::West => (rows, cols),
+    };
+    let mut moved = 0;
+    for lane in 0..lanes {
+        // Position of the i-th cell in the lane, counting from the far wall
+        let at = |i: usize| match dir {
+            Direction::North => (i, lane),
+            Direction::South => (rows - 1 - i, lane),
+            Direction::West => (lane, i),
+            Direction::East => (lane, cols - 1 - i),
+        };
+        let mut free = 0;
+        for i in 0..len {
+            let cell = &grid[at(i)];
+            if is_mobile(cell) {
+                if free != i {
+                    grid.swap(at(free), at(i));
+                    moved += 1;
+                }
+                free += 1;
+            } else if !is_empty(cell) {
+                free = i + 1;
+            }
+        }
+    }
+    moved
+}
+
+// Double-buffered stepping of a rule over a grid
+pub struct Automaton<T> {
+    current: Grid<T>,
+    next: Grid<T>,
+    neighbourhood: Neighbourhood,
+    generation: usize,
+}
+
+impl<T: Clone + PartialEq> Automaton<T> {
+    pub fn new(grid: Grid<T>, neighbourhood: Neighbourhood) -> Self {
+        Self { next: grid.clone(), current: grid, neighbourhood, generation: 0 }
+    }
+
+    pub fn grid(&self) -> &Grid<T> {
+        &self.current
+    }
+
+    pub fn into_grid(self) -> Grid<T> {
+        self.current
+    }
+
+    pub fn generation(&self) -> usize {
+        self.generation
+    }
+
+    // Advances one generation, returning how many cells changed
+    pub fn step<F>(&mut self, mut rule: F) -> usize
+    where
+        F: FnMut((usize, usize), &T, &[&T]) -> T,
+    {
+        let size = self.current.size();
+        let mut neighbours = Vec::with_capacity(8);
+        let mut changes = 0;
+        for (row, col, cell) in self.next.iter_mut() {
+            let old = &self.current[(row, col)];
+            neighbours.clear();
+            neighbours.extend(
+                self.neighbourhood.positions((row, col), size).map(|n| &self.current[n])
+            );
+            *cell = rule((row, col), old, &neighbours);
+            if cell != old {
+                changes += 1;
+            }
+        }
+        std::mem::swap(&mut self.current, &mut self.next);
+        self.generation += 1;
+        changes
+    }
+
+    // Steps until a generation changes nothing, returning the generation reached
+    pub fn run_until_stable<F>(&mut self, mut rule: F) -> usize
+    where
+        F: FnMut((usize, usize), &T, &[&T]) -> T,
+    {
+        while self.step(&mut rule) != 0 {}
+        self.generation
+    }
+
+    // Tilts the current generation in place, returning how many cells moved
+    pub fn sweep<M, E>(&mut self, dir: Direction, is_mobile: M, is_empty: E) -> usize
+    where
+        M: Fn(&T) -> bool,
+        E: Fn(&T) -> bool,
+    {
+        tilt(&mut self.current, dir, is_mobile, is_empty)
+    }
+}
+
+impl<T: Clone + Hash + Eq> Automaton<T> {
+    // The cycle that repeated application of the rule falls into, counted
+    // from the current generation
+    pub fn find_cycle<F>(&self, mut rule: F) -> Cycle
+    where
+        F: FnMut((usize, usize), &T, &[&T]) -> T,
+    {
+        let neighbourhood = self.neighbourhood;
+        let (cycle, _) = cycle::hashed(self.current.clone(), |grid| {
+            next_generation(grid, neighbourhood, &mut rule)
+        });
+        cycle
+    }
+
+    // Jumps straight to generation n, using cycle detection to skip ahead
+    pub fn advance_to<F>(&mut self, n: usize, mut rule: F)
+    where
+        F: FnMut((usize, usize), &T, &[&T]) -> T,
+    {
+        assert!(n >= self.generation, "cannot go back in time");
+        let neighbourhood = self.neighbourhood;
+        let steps = n - self.generation;
+        self.current = cycle::state_at(self.current.clone(), steps, |grid| {
+            next_generation(grid, neighbourhood, &mut rule)
+        });
+        self.generation = n;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{tilt, Automaton, Neighbourhood};
+    use crate::direction::Direction;
+    use crate::grid::Grid;
+
+    fn life(_: (usize, usize), cell: &bool, neighbours: &[&bool]) -> bool {
+        let alive = neighbours.iter().filter(|&&&n| n).count();
+        alive == 3 || (*cell && alive == 2)
+    }
+
+    #[test]
+    pub fn test_tilt() {
+        let mut rocks = Grid::from_lines(&["O.#", ".O.", "O.O"]);
+        let moved = tilt(&mut rocks, Direction::North, |&c| c == 'O', |&c| c == '.');
+        assert_eq!(Grid::from_lines(&["OO#", "O.O", "..."]), rocks);
+        assert_eq!(3, moved);
+    }
+
+    #[test]
+    pub fn test_blinker() {
+        let start = Grid::from_lines(&[".....", "..#..", "..#..", "..#..", "....."]).map(|_, &c| c == '#');
+        let mut automaton = Automaton::new(start.clone(), Neighbourhood::Moore);
+        assert_eq!(4, automaton.step(life));
+        assert_eq!(4, automaton.step(life));
+        assert_eq!(&start, automaton.grid());
+        let cycle = automaton.find_cycle(life);
+        assert_eq!((0, 2), (cycle.start, cycle.len));
+        automaton.advance_to(1_000_000_001, life);
+        assert_ne!(&start, automaton.grid());
+    }
+}
@@ -6,7 +6,7 @@ fn extract_digits(s: &str) -> Vec<u32> {
         "five", "six", "seven", "eight", "nine"
     ];
     let mut digits: Vec<u32> = Vec::new();
-    for (i, c) in s.chars().enumerate() {
+    for (i, c) in s.char_indices() {
         if let Some(d) = c.to_digit(10) {
             digits.push(d);
             continue;
@@ -31,7 +31,7 @@ pub fn main() {
         }
 
         let map: RangeMap = line.parse().expect("Failed to parse range map");
-        chain.add(&source, &dest, map);
+        chain.try_add(&source, &dest, map).expect("Invalid range map");
     }
 
     let locations = seeds.map(|n| chain.map("seed", n));
@@ -36,13 +36,13 @@ pub fn main() {
         }
 
         let map: RangeMap = line.parse().expect("Failed to parse range map");
-        chain.add(&source, &dest, map);
+        chain.try_add(&source, &dest, map).expect("Invalid range map");
     }
 
-    let locations = seed_ranges.iter().flat_map(|range| {
-        chain.map_range("seed", range)
-    });
-    let min_location = locations.min()
+    let locations = chain.map_ranges("seed", &seed_ranges);
+    let min_location = locations.iter()
+        .map(|range| range.start)
+        .min()
         .expect("No locations");
     println!("The minimum location is {}", min_location);
 }
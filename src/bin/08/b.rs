@@ -19,7 +19,7 @@ pub fn main() {
         .map(|s| Path::new(&routes, &path, s))
         .collect::<Vec<_>>();
 
-    let ends = paths.into_iter().flat_map(|mut path| path.steps_to_z());
+    let ends = paths.into_iter().flat_map(|path| path.steps_to_z());
     let lcm = ends.reduce(|acc, end| (acc * end) / acc.gcd(end))
         .expect("No solution");
     println!("It takes {} steps to reach all ends simultaneously", lcm);
@@ -1,24 +1,12 @@
 use aoc::{input_arg, read_char_grid};
-use aoc::pipes::{Matrix, Pipe};
+use aoc::pipes::Matrix;
 
 pub fn main() {
     let grid = read_char_grid(&input_arg());
     let mut matrix = Matrix::new(&grid);
     _ = matrix.furthest();
-    let (rows, cols) = grid.size();
-    let mut empty: usize = 0;
-    for row in 0..rows {
-        let mut inside = false;
-        for col in 0..cols {
-            if matrix.visited(row, col) {
-                match matrix[(row, col)] {
-                    Pipe::NS | Pipe::NW | Pipe::NE => inside = !inside,
-                    _ => {},
-                }
-            } else if inside {
-                empty += 1;
-            }
-        }
-    }
-    println!("The contained area has {} cells", empty);
+    let area = matrix.loop_polygon().interior_points()
+        .expect("Loop encloses no area") as usize;
+    assert_eq!(area, matrix.enclosed_area(), "Pick's theorem and flood fill disagree");
+    println!("The contained area has {} cells", area);
 }
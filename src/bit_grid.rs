@@ -0,0 +1,269 @@
+use std::ops::{BitAndAssign, BitOrAssign, Index};
+
+use super::grid::Grid;
+
+const BITS: usize = u64::BITS as usize;
+
+/*
+ * A grid of booleans packed 64 to a word. Each row starts on a fresh word so
+ * rows can be shifted independently, and any bits past the last column are
+ * kept clear so that counts stay accurate.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BitGrid {
+    words: Vec<u64>,
+    rows: usize,
+    cols: usize,
+    stride: usize,
+}
+
+impl BitGrid {
+    pub fn new(rows: usize, cols: usize) -> Self {
+        let stride = cols.div_ceil(BITS);
+        Self { words: vec![0; rows * stride], rows, cols, stride }
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        (self.rows, self.cols)
+    }
+
+    fn locate(&self, (row, col): (usize, usize)) -> (usize, u64) {
+        assert!(row < self.rows && col < self.cols, "index out of bounds");
+        (row * self.stride + col / BITS, 1 << (col % BITS))
+    }
+
+    pub fn get(&self, index: (usize, usize)) -> bool {
+        let (word, mask) = self.locate(index);
+        self.words[word] & mask != 0
+    }
+
+    pub fn set(&mut self, index: (usize, usize), value: bool) {
+        let (word, mask) = self.locate(index);
+        if value {
+            self.words[word] |= mask;
+        } else {
+            self.words[word] &= !mask;
+        }
+    }
+
+    // Number of cells that are set
+    pub fn count(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&w| w == 0)
+    }
+
+    pub fn clear(&mut self) {
+        self.words.fill(0);
+    }
+
+    pub fn union(&self, other: &BitGrid) -> BitGrid {
+        let mut result = self.clone();
+        result |= other;
+        result
+    }
+
+    pub fn intersection(&self, other: &BitGrid) -> BitGrid {
+        let mut result = self.clone();
+        result &= other;
+        result
+    }
+
+    pub fn difference(&self, other: &BitGrid) -> BitGrid {
+        self.check_size(other);
+        let mut result = self.clone();
+        for (w, o) in result.words.iter_mut().zip(other.words.iter()) {
+            *w &= !o;
+        }
+        result
+    }
+
+    pub fn complement(&self) -> BitGrid {
+        let mut result = self.clone();
+        for w in result.words.iter_mut() {
+            *w = !*w;
+        }
+        result.mask_rows();
+        result
+    }
+
+    /*
+     * Moves every cell by (drow, dcol). Cells moved off the edge are lost and
+     * the vacated cells are cleared.
+     */
+    pub fn shifted(&self, drow: isize, dcol: isize) -> BitGrid {
+        let mut result = BitGrid::new(self.rows, self.cols);
+        for row in 0..self.rows {
+            let Some(dest) = row.checked_add_signed(drow).filter(|&r| r < self.rows) else {
+                continue;
+            };
+            let src = &self.words[row * self.stride..(row + 1) * self.stride];
+            let out = &mut result.words[dest * self.stride..(dest + 1) * self.stride];
+            shift_row(src, out, dcol);
+        }
+        result.mask_rows();
+        result
+    }
+
+    // Cells orthogonally adjacent to any set cell, such as the positions
+    // reachable in one more step
+    pub fn spread(&self) -> BitGrid {
+        let mut result = self.shifted(-1, 0);
+        result |= &self.shifted(1, 0);
+        result |= &self.shifted(0, -1);
+        result |= &self.shifted(0, 1);
+        result
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { grid: self, x: 0, y: 0 }
+    }
+
+    // (row, col) of every set cell, skipping clear words
+    pub fn ones(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.words.iter().enumerate().flat_map(move |(i, &word)| {
+            let row = i / self.stride;
+            let base = (i % self.stride) * BITS;
+            let mut word = word;
+            std::iter::from_fn(move || {
+                if word == 0 {
+                    return None;
+                }
+                let bit = word.trailing_zeros() as usize;
+                word &= word - 1;
+                Some((row, base + bit))
+            })
+        })
+    }
+
+    pub fn to_grid(&self) -> Grid<bool> {
+        let cells = self.iter().map(|(_, _, set)| set).collect();
+        Grid::new_with_cells(cells, self.rows, self.cols)
+    }
+
+    fn check_size(&self, other: &BitGrid) {
+        assert_eq!(self.size(), other.size(), "grid sizes differ");
+    }
+
+    fn mask_rows(&mut self) {
+        let extra = self.stride * BITS - self.cols;
+        if extra == 0 {
+            return;
+        }
+        let mask = u64::MAX >> extra;
+        for row in 0..self.rows {
+            self.words[(row + 1) * self.stride - 1] &= mask;
+        }
+    }
+}
+
+// Shifts the bits of one row towards higher columns when dcol is positive
+fn shift_row(src: &[u64], out: &mut [u64], dcol: isize) {
+    let len = src.len() as isize;
+    let words = dcol.div_euclid(BITS as isize);
+    let bits = dcol.rem_euclid(BITS as isize) as u32;
+    let word_at = |i: isize| if (0..len).contains(&i) { src[i as usize] } else { 0 };
+    for (w, out) in out.iter_mut().enumerate() {
+        let from = w as isize - words;
+        *out = if bits == 0 {
+            word_at(from)
+        } else {
+            (word_at(from) << bits) | (word_at(from - 1) >> (BITS as u32 - bits))
+        };
+    }
+}
+
+impl BitOrAssign<&BitGrid> for BitGrid {
+    fn bitor_assign(&mut self, rhs: &BitGrid) {
+        self.check_size(rhs);
+        for (w, o) in self.words.iter_mut().zip(rhs.words.iter()) {
+            *w |= o;
+        }
+    }
+}
+
+impl BitAndAssign<&BitGrid> for BitGrid {
+    fn bitand_assign(&mut self, rhs: &BitGrid) {
+        self.check_size(rhs);
+        for (w, o) in self.words.iter_mut().zip(rhs.words.iter()) {
+            *w &= o;
+        }
+    }
+}
+
+impl Index<(usize, usize)> for BitGrid {
+    type Output = bool;
+
+    fn index(&self, index: (usize, usize)) -> &Self::Output {
+        if self.get(index) { &true } else { &false }
+    }
+}
+
+impl From<&Grid<bool>> for BitGrid {
+    fn from(grid: &Grid<bool>) -> Self {
+        let (rows, cols) = grid.size();
+        let mut bits = BitGrid::new(rows, cols);
+        for (row, col, &set) in grid.iter() {
+            if set {
+                bits.set((row, col), true);
+            }
+        }
+        bits
+    }
+}
+
+pub struct Iter<'a> {
+    grid: &'a BitGrid,
+    x: usize,
+    y: usize,
+}
+
+impl Iterator for Iter<'_> {
+    type Item = (usize, usize, bool);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (rows, cols) = self.grid.size();
+        let (x, y) = (self.x, self.y);
+        if y == rows || cols == 0 {
+            return None;
+        }
+        let item = self.grid.get((y, x));
+        if x + 1 < cols {
+            self.x += 1;
+        } else {
+            self.x = 0;
+            self.y += 1;
+        }
+        Some((y, x, item))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BitGrid;
+
+    #[test]
+    pub fn test_shift_across_words() {
+        let mut grid = BitGrid::new(3, 130);
+        grid.set((1, 63), true);
+        grid.set((1, 129), true);
+        let right = grid.shifted(1, 1);
+        assert_eq!(vec![(2, 64)], right.ones().collect::<Vec<_>>());
+        let left = grid.shifted(0, -65);
+        assert_eq!(vec![(1, 64)], left.ones().collect::<Vec<_>>());
+    }
+
+    #[test]
+    pub fn test_spread() {
+        let mut grid = BitGrid::new(3, 3);
+        grid.set((0, 0), true);
+        grid.set((2, 2), true);
+        let spread = grid.spread();
+        assert_eq!(4, spread.count());
+        assert!(spread[(0, 1)] && spread[(1, 0)] && spread[(1, 2)] && spread[(2, 1)]);
+        assert_eq!(3, spread.union(&grid).complement().count());
+        assert!(spread.intersection(&grid).is_empty());
+    }
+}
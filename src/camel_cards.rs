@@ -1,5 +1,6 @@
 use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::fmt;
 use std::ops::AddAssign;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -20,9 +21,9 @@ pub enum Kind {
     FiveOfAKind,
 }
 
-impl Kind {
-    pub fn to_string(&self) -> String {
-        match *self {
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match *self {
             Kind::HighCard => "High Card",
             Kind::OnePair => "One Pair",
             Kind::TwoPair => "Two Pair",
@@ -30,7 +31,8 @@ impl Kind {
             Kind::FullHouse => "Full House",
             Kind::FourOfAKind => "Four of a Kind",
             Kind::FiveOfAKind => "Five of a Kind",
-        }.to_owned()
+        };
+        f.write_str(name)
     }
 }
 
@@ -152,15 +154,17 @@ impl Hand {
     pub fn kind(&self) -> Kind {
         self.kind
     }
+}
 
-    pub fn to_string(&self) -> String {
-        format!("{}{}{}{}{} ({}) {}",
+impl fmt::Display for Hand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}{}{}{} ({}) {}",
             self.cards[0].to_char(),
             self.cards[1].to_char(),
             self.cards[2].to_char(),
             self.cards[3].to_char(),
             self.cards[4].to_char(),
-            self.kind.to_string(),
+            self.kind,
             self.bid
         )
     }
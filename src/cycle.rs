@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+// A sequence of states that reaches `start` and then repeats every `len` steps
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub len: usize,
+}
+
+impl Cycle {
+    pub fn new(start: usize, len: usize) -> Self {
+        Self { start, len }
+    }
+
+    // The earliest step with the same state as step n
+    pub fn offset(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.len
+        }
+    }
+
+    // Computes the state at step n by only simulating up to its offset
+    pub fn extrapolate<S, F>(&self, initial: S, n: usize, mut step: F) -> S
+    where
+        F: FnMut(&S) -> S,
+    {
+        let mut state = initial;
+        for _ in 0..self.offset(n) {
+            state = step(&state);
+        }
+        state
+    }
+}
+
+/*
+ * Brent's algorithm only keeps two states alive at a time, at the cost of
+ * stepping through the sequence roughly three times. Prefer it when states
+ * are large, such as whole grids.
+ */
+pub fn brent<S, F>(initial: S, mut step: F) -> Cycle
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    // Find the cycle length by teleporting the tortoise to the hare at
+    // increasing powers of two
+    let mut power = 1;
+    let mut len = 1;
+    let mut tortoise = initial.clone();
+    let mut hare = step(&initial);
+    while tortoise != hare {
+        if power == len {
+            tortoise = hare.clone();
+            power *= 2;
+            len = 0;
+        }
+        hare = step(&hare);
+        len += 1;
+    }
+
+    // Find the cycle start by walking two states len apart in lockstep
+    let mut tortoise = initial.clone();
+    let mut hare = initial;
+    for _ in 0..len {
+        hare = step(&hare);
+    }
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Cycle::new(start, len)
+}
+
+/*
+ * Records every state until one repeats. Returns the cycle along with the
+ * states for steps 0 to start + len - 1, so any later step can be looked up
+ * through Cycle::offset without simulating again.
+ */
+pub fn hashed<S, F>(initial: S, mut step: F) -> (Cycle, Vec<S>)
+where
+    S: Clone + Hash + Eq,
+    F: FnMut(&S) -> S,
+{
+    let mut seen: HashMap<S, usize> = HashMap::new();
+    let mut states: Vec<S> = Vec::new();
+    let mut state = initial;
+    loop {
+        if let Some(&start) = seen.get(&state) {
+            let cycle = Cycle::new(start, states.len() - start);
+            return (cycle, states);
+        }
+        seen.insert(state.clone(), states.len());
+        let next = step(&state);
+        states.push(state);
+        state = next;
+    }
+}
+
+// Finds the state at step n, which may be far beyond the end of the cycle
+pub fn state_at<S, F>(initial: S, n: usize, step: F) -> S
+where
+    S: Clone + Hash + Eq,
+    F: FnMut(&S) -> S,
+{
+    let (cycle, mut states) = hashed(initial, step);
+    states.swap_remove(cycle.offset(n))
+}
+
+#[cfg(test)]
+mod test {
+    use super::{brent, hashed, state_at, Cycle};
+
+    fn step(n: &u64) -> u64 {
+        // Enters a loop after a short lead-in
+        (n * n + 1) % 1000
+    }
+
+    #[test]
+    pub fn test_brent_matches_hashed() {
+        let (cycle, states) = hashed(0, step);
+        assert_eq!(cycle, brent(0, step));
+        assert_eq!(cycle.start + cycle.len, states.len());
+        assert_eq!(states[cycle.start], step(states.last().unwrap()));
+    }
+
+    #[test]
+    pub fn test_extrapolate() {
+        let n = 1_000_000_000;
+        let cycle = brent(0, step);
+        let mut expected = 0;
+        for _ in 0..(cycle.start + 3 * cycle.len + 5) {
+            expected = step(&expected);
+        }
+        let far = cycle.start + 1_000 * cycle.len + 5;
+        assert_eq!(expected, cycle.extrapolate(0, far, step));
+        assert_eq!(cycle.extrapolate(0, n, step), state_at(0, n, step));
+    }
+
+    #[test]
+    pub fn test_offset() {
+        let cycle = Cycle::new(3, 4);
+        assert_eq!(2, cycle.offset(2));
+        assert_eq!(3, cycle.offset(7));
+        assert_eq!(6, cycle.offset(1_000_000_002));
+    }
+}
@@ -0,0 +1,205 @@
+use std::str::FromStr;
+
+use super::point::Point2D;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidDirection,
+}
+
+/*
+ * Directions use screen coordinates, matching grid rows and columns: north is
+ * towards row 0, so its delta has a negative y.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Direction {
+    // Clockwise from north
+    pub fn all() -> [Direction; 4] {
+        [Direction::North, Direction::East, Direction::South, Direction::West]
+    }
+
+    pub fn turn_left(&self) -> Direction {
+        match *self {
+            Direction::North => Direction::West,
+            Direction::East => Direction::North,
+            Direction::South => Direction::East,
+            Direction::West => Direction::South,
+        }
+    }
+
+    pub fn turn_right(&self) -> Direction {
+        match *self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+
+    pub fn opposite(&self) -> Direction {
+        self.turn_left().turn_left()
+    }
+
+    pub fn delta(&self) -> Point2D<isize> {
+        match *self {
+            Direction::North => Point2D::new(0, -1),
+            Direction::East => Point2D::new(1, 0),
+            Direction::South => Point2D::new(0, 1),
+            Direction::West => Point2D::new(-1, 0),
+        }
+    }
+
+    // The neighbouring (row, col) in this direction, if it is inside a grid
+    // of the given size
+    pub fn step(&self, (row, col): (usize, usize), (rows, cols): (usize, usize))
+        -> Option<(usize, usize)>
+    {
+        let delta = self.delta();
+        let row = row.checked_add_signed(delta.y).filter(|&r| r < rows)?;
+        let col = col.checked_add_signed(delta.x).filter(|&c| c < cols)?;
+        Some((row, col))
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = ParseError;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value.to_ascii_uppercase() {
+            'N' | 'U' | '^' => Ok(Direction::North),
+            'E' | 'R' | '>' => Ok(Direction::East),
+            'S' | 'D' | 'V' => Ok(Direction::South),
+            'W' | 'L' | '<' => Ok(Direction::West),
+            _ => Err(ParseError::InvalidDirection),
+        }
+    }
+}
+
+impl FromStr for Direction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cs = s.trim().chars();
+        match (cs.next(), cs.next()) {
+            (Some(c), None) => Direction::try_from(c),
+            _ => Err(ParseError::InvalidDirection),
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl Direction8 {
+    // Clockwise from north
+    pub fn all() -> [Direction8; 8] {
+        use Direction8::*;
+        [North, NorthEast, East, SouthEast, South, SouthWest, West, NorthWest]
+    }
+
+    fn index(&self) -> usize {
+        Direction8::all().iter().position(|d| d == self).unwrap()
+    }
+
+    // Turns 45 degrees anticlockwise
+    pub fn turn_left(&self) -> Direction8 {
+        Direction8::all()[(self.index() + 7) % 8]
+    }
+
+    // Turns 45 degrees clockwise
+    pub fn turn_right(&self) -> Direction8 {
+        Direction8::all()[(self.index() + 1) % 8]
+    }
+
+    pub fn opposite(&self) -> Direction8 {
+        Direction8::all()[(self.index() + 4) % 8]
+    }
+
+    pub fn delta(&self) -> Point2D<isize> {
+        use Direction8::*;
+        let (x, y) = match *self {
+            North => (0, -1),
+            NorthEast => (1, -1),
+            East => (1, 0),
+            SouthEast => (1, 1),
+            South => (0, 1),
+            SouthWest => (-1, 1),
+            West => (-1, 0),
+            NorthWest => (-1, -1),
+        };
+        Point2D::new(x, y)
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(dir: Direction) -> Self {
+        match dir {
+            Direction::North => Direction8::North,
+            Direction::East => Direction8::East,
+            Direction::South => Direction8::South,
+            Direction::West => Direction8::West,
+        }
+    }
+}
+
+impl FromStr for Direction8 {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use Direction8::*;
+        match s.trim().to_ascii_uppercase().as_str() {
+            "N" | "U" | "^" => Ok(North),
+            "NE" => Ok(NorthEast),
+            "E" | "R" | ">" => Ok(East),
+            "SE" => Ok(SouthEast),
+            "S" | "D" | "V" => Ok(South),
+            "SW" => Ok(SouthWest),
+            "W" | "L" | "<" => Ok(West),
+            "NW" => Ok(NorthWest),
+            _ => Err(ParseError::InvalidDirection),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Direction, Direction8};
+    use crate::point::Point2D;
+
+    #[test]
+    pub fn test_parse() {
+        let dirs: Vec<Direction> = "U>sW".chars().map(|c| c.try_into().unwrap()).collect();
+        assert_eq!(Direction::all().to_vec(), dirs);
+        assert_eq!(Ok(Direction::South), "v".parse());
+        assert!("x".parse::<Direction>().is_err());
+        assert_eq!(Ok(Direction8::SouthWest), "sw".parse());
+    }
+
+    #[test]
+    pub fn test_turns() {
+        for dir in Direction::all() {
+            assert_eq!(dir, dir.turn_left().turn_right());
+            assert_eq!(Point2D::new(0, 0), dir.delta() + dir.opposite().delta());
+        }
+        assert_eq!(Direction8::North, Direction8::NorthWest.turn_right());
+        assert_eq!(Direction8::SouthEast, Direction8::NorthWest.opposite());
+        assert_eq!(Some((1, 0)), Direction::South.step((0, 0), (2, 2)));
+        assert_eq!(None, Direction::West.step((0, 0), (2, 2)));
+    }
+}
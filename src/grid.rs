@@ -1,6 +1,11 @@
-use std::ops::{Index, IndexMut};
+use std::iter::Enumerate;
+use std::ops::{Index, IndexMut, Range};
+use std::{slice, vec};
 
-#[derive(Clone)]
+use super::point::Point2D;
+use super::rect::Rect;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Grid<T> {
     cells: Vec<T>,
     rows: usize,
@@ -16,6 +21,24 @@ impl<T> Grid<T> {
         (self.rows, self.cols)
     }
 
+    // Whether the point, with x as the column and y as the row, is on the grid
+    pub fn contains(&self, p: Point2D<isize>) -> bool {
+        self.index_of(p).is_some()
+    }
+
+    // The (row, col) for a point, if it is on the grid
+    pub fn index_of(&self, p: Point2D<isize>) -> Option<(usize, usize)> {
+        p.to_row_col().filter(|&(row, col)| row < self.rows && col < self.cols)
+    }
+
+    pub fn get(&self, p: Point2D<isize>) -> Option<&T> {
+        self.index_of(p).map(|index| &self[index])
+    }
+
+    pub fn get_mut(&mut self, p: Point2D<isize>) -> Option<&mut T> {
+        self.index_of(p).map(|index| &mut self[index])
+    }
+
     pub fn enumerate<F>(&self, mut f: F)
     where
         F: FnMut((usize, usize), &T),
@@ -42,12 +65,161 @@ impl<T> Grid<T> {
         Grid::new_with_cells(cells, self.rows, self.cols)
     }
 
-    pub fn iter(&self) -> Iter<T> {
-        Iter {
-            grid: self,
-            x: 0,
-            y: 0,
+    pub fn iter(&self) -> Iter<'_, T> {
+        Iter { cells: self.cells.iter().enumerate(), cols: self.cols }
+    }
+
+    pub fn iter_mut(&mut self) -> IterMut<'_, T> {
+        IterMut { cells: self.cells.iter_mut().enumerate(), cols: self.cols }
+    }
+
+    // Every (row, col) in row-major order
+    pub fn positions(&self) -> Positions {
+        Positions { indices: 0..self.cells.len(), cols: self.cols }
+    }
+
+    // The first (row, col) in row-major order whose cell matches
+    pub fn find<P>(&self, predicate: P) -> Option<(usize, usize)>
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.cells.iter()
+            .position(predicate)
+            .map(|i| (i / self.cols, i % self.cols))
+    }
+
+    pub fn position_of(&self, value: &T) -> Option<(usize, usize)>
+    where
+        T: PartialEq,
+    {
+        self.find(|cell| cell == value)
+    }
+
+    pub fn swap(&mut self, a: (usize, usize), b: (usize, usize)) {
+        self.cells.swap(a.0 * self.cols + a.1, b.0 * self.cols + b.1);
+    }
+
+    // Replaces every cell with a k x k block of cells
+    pub fn upscale<U, F>(&self, k: usize, mut expand: F) -> Grid<U>
+    where
+        F: FnMut((usize, usize), &T) -> Grid<U>,
+    {
+        let mut cells = Vec::with_capacity(self.cells.len() * k * k);
+        for row in 0..self.rows {
+            let mut blocks: Vec<_> = self[row].iter().enumerate()
+                .map(|(col, cell)| {
+                    let block = expand((row, col), cell);
+                    assert_eq!((k, k), block.size(), "block has wrong size");
+                    block.cells.into_iter()
+                })
+                .collect();
+            for _ in 0..k {
+                for block in blocks.iter_mut() {
+                    cells.extend(block.by_ref().take(k));
+                }
+            }
+        }
+        Grid::new_with_cells(cells, self.rows * k, self.cols * k)
+    }
+
+    // Reverses upscale by reducing each k x k block, given in row-major
+    // order, to a single cell
+    pub fn downsample<U, F>(&self, k: usize, mut reduce: F) -> Grid<U>
+    where
+        F: FnMut((usize, usize), &[&T]) -> U,
+    {
+        assert!(self.rows.is_multiple_of(k) && self.cols.is_multiple_of(k), "size is not a multiple of k");
+        let (rows, cols) = (self.rows / k, self.cols / k);
+        let mut cells = Vec::with_capacity(rows * cols);
+        let mut block = Vec::with_capacity(k * k);
+        for row in 0..rows {
+            for col in 0..cols {
+                block.clear();
+                for y in row * k..(row + 1) * k {
+                    block.extend(self[y][col * k..(col + 1) * k].iter());
+                }
+                cells.push(reduce((row, col), &block));
+            }
+        }
+        Grid::new_with_cells(cells, rows, cols)
+    }
+
+    // Inserts a row before `row`, so it becomes row `row`
+    pub fn insert_row(&mut self, row: usize, cells: Vec<T>) {
+        assert!(row <= self.rows, "row out of bounds");
+        if self.rows == 0 {
+            self.cols = cells.len();
+        } else {
+            assert_eq!(self.cols, cells.len(), "row has wrong width");
         }
+        let start = row * self.cols;
+        self.cells.splice(start..start, cells);
+        self.rows += 1;
+    }
+
+    // Inserts a column before `col`, so it becomes column `col`
+    pub fn insert_col(&mut self, col: usize, cells: Vec<T>) {
+        assert!(col <= self.cols, "column out of bounds");
+        if self.cols == 0 {
+            self.rows = cells.len();
+        } else {
+            assert_eq!(self.rows, cells.len(), "column has wrong height");
+        }
+        let mut old = std::mem::take(&mut self.cells).into_iter();
+        let mut new = Vec::with_capacity(self.rows * (self.cols + 1));
+        for cell in cells {
+            new.extend(old.by_ref().take(col));
+            new.push(cell);
+            new.extend(old.by_ref().take(self.cols - col));
+        }
+        self.cells = new;
+        self.cols += 1;
+    }
+
+    pub fn remove_row(&mut self, row: usize) -> Vec<T> {
+        assert!(row < self.rows, "row out of bounds");
+        let start = row * self.cols;
+        let removed = self.cells.drain(start..start + self.cols).collect();
+        self.rows -= 1;
+        removed
+    }
+
+    pub fn remove_col(&mut self, col: usize) -> Vec<T> {
+        assert!(col < self.cols, "column out of bounds");
+        let mut removed = Vec::with_capacity(self.rows);
+        let mut kept = Vec::with_capacity(self.rows * (self.cols - 1));
+        for (i, cell) in std::mem::take(&mut self.cells).into_iter().enumerate() {
+            if i % self.cols == col {
+                removed.push(cell);
+            } else {
+                kept.push(cell);
+            }
+        }
+        self.cells = kept;
+        self.cols -= 1;
+        removed
+    }
+
+    // Keeps only the cells within the given row and column ranges
+    pub fn crop(&mut self, rows: Range<usize>, cols: Range<usize>) {
+        assert!(rows.start <= rows.end && rows.end <= self.rows, "rows out of bounds");
+        assert!(cols.start <= cols.end && cols.end <= self.cols, "columns out of bounds");
+        let width = self.cols;
+        let mut kept = Vec::with_capacity(rows.len() * cols.len());
+        for (i, cell) in std::mem::take(&mut self.cells).into_iter().enumerate() {
+            if rows.contains(&(i / width)) && cols.contains(&(i % width)) {
+                kept.push(cell);
+            }
+        }
+        self.cells = kept;
+        self.rows = rows.len();
+        self.cols = cols.len();
+    }
+
+    // Like crop, but keeping the cells inside a rectangle, where x is the
+    // column and y the row
+    pub fn crop_rect(&mut self, rect: &Rect<usize>) {
+        self.crop(rect.min.y..rect.max.y + 1, rect.min.x..rect.max.x + 1);
     }
 }
 
@@ -91,28 +263,324 @@ impl<T: Default> Grid<T> {
     }
 }
 
+impl<T: Clone> Grid<T> {
+    // Surrounds the grid with `border` rows and columns of `value` on every
+    // side, which lets neighbour lookups skip bounds checks
+    pub fn pad(&mut self, border: usize, value: T) {
+        let cols = self.cols + 2 * border;
+        let rows = self.rows + 2 * border;
+        let mut old = std::mem::take(&mut self.cells).into_iter();
+        let mut new = Vec::with_capacity(rows * cols);
+        new.resize(border * cols, value.clone());
+        for _ in 0..self.rows {
+            new.resize(new.len() + border, value.clone());
+            new.extend(old.by_ref().take(self.cols));
+            new.resize(new.len() + border, value.clone());
+        }
+        new.resize(rows * cols, value);
+        self.cells = new;
+        self.rows = rows;
+        self.cols = cols;
+    }
+}
+
+impl Grid<char> {
+    // One row per line, which must all be the same width
+    pub fn from_lines<S: AsRef<str>>(lines: &[S]) -> Self {
+        let cols = lines.first().map_or(0, |line| line.as_ref().chars().count());
+        let mut cells = Vec::with_capacity(lines.len() * cols);
+        for line in lines {
+            let len = cells.len();
+            cells.extend(line.as_ref().chars());
+            if cells.len() - len != cols {
+                panic!("inconsistent grid width");
+            }
+        }
+        Grid::new_with_cells(cells, lines.len(), cols)
+    }
+
+    // Splits each row into runs of digits, runs of letters and single symbol
+    // cells, skipping blank cells. Tokens never continue onto the next row.
+    pub fn tokens(&self, blank: char) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        for row in 0..self.rows {
+            let cells = &self[row];
+            let mut col = 0;
+            while col < self.cols {
+                let c = cells[col];
+                let kind = if c == blank {
+                    col += 1;
+                    continue;
+                } else if c.is_ascii_digit() {
+                    TokenKind::Number
+                } else if c.is_alphabetic() {
+                    TokenKind::Word
+                } else {
+                    TokenKind::Symbol
+                };
+                let start = col;
+                col += 1;
+                if kind != TokenKind::Symbol {
+                    while col < self.cols && kind.accepts(cells[col]) {
+                        col += 1;
+                    }
+                }
+                let text = cells[start..col].iter().collect();
+                tokens.push(Token { kind, text, row, cols: start..col });
+            }
+        }
+        tokens
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum TokenKind {
+    Number,
+    Word,
+    Symbol,
+}
+
+impl TokenKind {
+    fn accepts(&self, c: char) -> bool {
+        match *self {
+            TokenKind::Number => c.is_ascii_digit(),
+            TokenKind::Word => c.is_alphabetic(),
+            TokenKind::Symbol => false,
+        }
+    }
+}
+
+// A horizontal run of cells within a single row
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub text: String,
+    pub row: usize,
+    pub cols: Range<usize>,
+}
+
+impl Token {
+    pub fn value(&self) -> Option<usize> {
+        match self.kind {
+            TokenKind::Number => self.text.parse().ok(),
+            _ => None,
+        }
+    }
+
+    pub fn symbol(&self) -> Option<char> {
+        match self.kind {
+            TokenKind::Symbol => self.text.chars().next(),
+            _ => None,
+        }
+    }
+
+    pub fn contains(&self, (row, col): (usize, usize)) -> bool {
+        row == self.row && self.cols.contains(&col)
+    }
+
+    // Cells touching the token, including diagonally, within a grid of the
+    // given (rows, cols) size
+    pub fn adjacent(&self, (rows, cols): (usize, usize)) -> Vec<(usize, usize)> {
+        let top = self.row.saturating_sub(1);
+        let bottom = (self.row + 2).min(rows);
+        let left = self.cols.start.saturating_sub(1);
+        let right = (self.cols.end + 1).min(cols);
+        let mut cells = Vec::new();
+        for row in top..bottom {
+            for col in left..right {
+                if !self.contains((row, col)) {
+                    cells.push((row, col));
+                }
+            }
+        }
+        cells
+    }
+
+    // Whether the tokens touch without overlapping
+    pub fn is_adjacent(&self, other: &Token) -> bool {
+        let overlaps = self.row == other.row
+            && self.cols.start < other.cols.end
+            && other.cols.start < self.cols.end;
+        self.row.abs_diff(other.row) <= 1
+            && self.cols.start <= other.cols.end
+            && other.cols.start <= self.cols.end
+            && !overlaps
+    }
+}
+
+// Row-major cell iterators only differ in how the cells are borrowed
+macro_rules! cell_iterator {
+    ($name:ident, $item:ty, $($lt:lifetime)?) => {
+        impl<$($lt,)? T> Iterator for $name<$($lt,)? T> {
+            type Item = (usize, usize, $item);
+
+            fn next(&mut self) -> Option<Self::Item> {
+                let cols = self.cols;
+                self.cells.next().map(|(i, cell)| (i / cols, i % cols, cell))
+            }
+
+            fn size_hint(&self) -> (usize, Option<usize>) {
+                self.cells.size_hint()
+            }
+        }
+
+        impl<$($lt,)? T> DoubleEndedIterator for $name<$($lt,)? T> {
+            fn next_back(&mut self) -> Option<Self::Item> {
+                let cols = self.cols;
+                self.cells.next_back().map(|(i, cell)| (i / cols, i % cols, cell))
+            }
+        }
+
+        impl<$($lt,)? T> ExactSizeIterator for $name<$($lt,)? T> {}
+    };
+}
+
+// Yields (row, col, &cell) in row-major order
 pub struct Iter<'a, T> {
-    grid: &'a Grid<T>,
-    x: usize,
-    y: usize,
+    cells: Enumerate<slice::Iter<'a, T>>,
+    cols: usize,
+}
+
+cell_iterator!(Iter, &'a T, 'a);
+
+// Yields (row, col, &mut cell) in row-major order
+pub struct IterMut<'a, T> {
+    cells: Enumerate<slice::IterMut<'a, T>>,
+    cols: usize,
+}
+
+cell_iterator!(IterMut, &'a mut T, 'a);
+
+// Yields (row, col, cell) in row-major order
+pub struct IntoIter<T> {
+    cells: Enumerate<vec::IntoIter<T>>,
+    cols: usize,
 }
 
-impl<'a, T> Iterator for Iter<'a, T> {
+cell_iterator!(IntoIter, T,);
+
+impl<T> IntoIterator for Grid<T> {
+    type Item = (usize, usize, T);
+    type IntoIter = IntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter { cells: self.cells.into_iter().enumerate(), cols: self.cols }
+    }
+}
+
+impl<'a, T> IntoIterator for &'a Grid<T> {
     type Item = (usize, usize, &'a T);
+    type IntoIter = Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a, T> IntoIterator for &'a mut Grid<T> {
+    type Item = (usize, usize, &'a mut T);
+    type IntoIter = IterMut<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+pub struct Positions {
+    indices: Range<usize>,
+    cols: usize,
+}
+
+impl Iterator for Positions {
+    type Item = (usize, usize);
 
     fn next(&mut self) -> Option<Self::Item> {
-        let (rows, cols) = self.grid.size();
-        let (x, y) = (self.x, self.y);
-        if y == rows {
-            return None;
-        }
-        let item = &self.grid[y][x];
-        if x + 1 < cols {
-            self.x += 1;
-        } else {
-            self.x = 0;
-            self.y += 1;
+        self.indices.next().map(|i| (i / self.cols, i % self.cols))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.indices.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Positions {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.indices.next_back().map(|i| (i / self.cols, i % self.cols))
+    }
+}
+
+impl ExactSizeIterator for Positions {}
+
+#[cfg(test)]
+mod test {
+    use super::Grid;
+    use crate::point::Point2D;
+    use crate::rect::Rect;
+
+    fn numbered(rows: usize, cols: usize) -> Grid<usize> {
+        Grid::new_with_cells((0..rows * cols).collect(), rows, cols)
+    }
+
+    #[test]
+    pub fn test_insert_remove() {
+        let mut grid = numbered(2, 3);
+        grid.insert_row(1, vec![7, 8, 9]);
+        grid.insert_col(3, vec![10, 11, 12]);
+        assert_eq!((3, 4), grid.size());
+        assert_eq!(&[7, 8, 9, 11], &grid[1]);
+        assert_eq!(vec![10, 11, 12], grid.remove_col(3));
+        assert_eq!(vec![7, 8, 9], grid.remove_row(1));
+        assert_eq!(numbered(2, 3), grid);
+    }
+
+    #[test]
+    pub fn test_pad_crop() {
+        let mut grid = numbered(2, 3);
+        grid.pad(2, 99);
+        assert_eq!((6, 7), grid.size());
+        assert_eq!(99, grid[(1, 6)]);
+        assert_eq!(4, grid[(3, 3)]);
+        grid.crop(2..4, 2..5);
+        assert_eq!(numbered(2, 3), grid);
+        grid.pad(1, 0);
+        grid.crop_rect(&Rect::new(Point2D::new(1, 1), Point2D::new(3, 2)));
+        assert_eq!(numbered(2, 3), grid);
+    }
+
+    #[test]
+    pub fn test_iterators() {
+        let mut grid = numbered(2, 3);
+        for (row, col, cell) in grid.iter_mut() {
+            *cell += 10 * row + col;
         }
-        Some((y, x, item))
+        assert_eq!(Some((1, 2)), grid.position_of(&17));
+        assert_eq!(None, grid.find(|&n| n > 17));
+        assert_eq!(6, grid.iter().len());
+        assert_eq!(Some((1, 2, &17)), grid.iter().next_back());
+        assert_eq!(Some((0, 1)), grid.positions().nth(1));
+        let cells: Vec<_> = grid.into_iter().rev().map(|(_, _, n)| n).take(2).collect();
+        assert_eq!(vec![17, 15], cells);
+    }
+
+    #[test]
+    pub fn test_upscale_downsample() {
+        let grid = numbered(2, 3);
+        let big = grid.upscale(2, |_, &n| Grid::new_with_cells(vec![n; 4], 2, 2));
+        assert_eq!((4, 6), big.size());
+        assert_eq!(5, big[(3, 5)]);
+        assert_eq!(1, big[(1, 2)]);
+        let small = big.downsample(2, |_, block| block.iter().copied().sum::<usize>() / 4);
+        assert_eq!(grid, small);
+    }
+
+    #[test]
+    pub fn test_points() {
+        let mut grid = numbered(2, 3);
+        assert!(grid.contains(Point2D::new(2, 1)));
+        assert!(!grid.contains(Point2D::new(1, 2)));
+        assert!(!grid.contains(Point2D::new(-1, 0)));
+        assert_eq!(Some(&5), grid.get(Point2D::new(2, 1)));
+        *grid.get_mut(Point2D::new(0, 1)).unwrap() = 9;
+        assert_eq!(9, grid[(1, 0)]);
     }
 }
@@ -0,0 +1,242 @@
+use std::ops::{Index, IndexMut};
+
+use super::grid::Grid;
+use super::point::Point3D;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Axis {
+    X,
+    Y,
+    Z,
+}
+
+// A dense 3D grid, indexed by (x, y, z) and stored one z layer at a time
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Grid3<T> {
+    cells: Vec<T>,
+    xs: usize,
+    ys: usize,
+    zs: usize,
+}
+
+impl<T> Grid3<T> {
+    pub fn new_with_cells(cells: Vec<T>, xs: usize, ys: usize, zs: usize) -> Self {
+        assert_eq!(xs * ys * zs, cells.len(), "wrong number of cells");
+        Grid3 { cells, xs, ys, zs }
+    }
+
+    // (x, y, z) extents
+    pub fn size(&self) -> (usize, usize, usize) {
+        (self.xs, self.ys, self.zs)
+    }
+
+    pub fn contains(&self, (x, y, z): (usize, usize, usize)) -> bool {
+        x < self.xs && y < self.ys && z < self.zs
+    }
+
+    fn offset(&self, (x, y, z): (usize, usize, usize)) -> usize {
+        assert!(self.contains((x, y, z)), "index out of bounds");
+        (z * self.ys + y) * self.xs + x
+    }
+
+    fn position(&self, i: usize) -> (usize, usize, usize) {
+        (i % self.xs, (i / self.xs) % self.ys, i / (self.xs * self.ys))
+    }
+
+    pub fn get(&self, index: (usize, usize, usize)) -> Option<&T> {
+        if self.contains(index) {
+            Some(&self.cells[self.offset(index)])
+        } else {
+            None
+        }
+    }
+
+    // Yields (x, y, z, &cell), with x varying fastest
+    pub fn iter(&self) -> impl Iterator<Item = (usize, usize, usize, &T)> {
+        self.cells.iter().enumerate().map(|(i, cell)| {
+            let (x, y, z) = self.position(i);
+            (x, y, z, cell)
+        })
+    }
+
+    pub fn map<F, U>(&self, mut f: F) -> Grid3<U>
+    where
+        F: FnMut((usize, usize, usize), &T) -> U,
+    {
+        let cells = self.cells.iter().enumerate()
+            .map(|(i, cell)| f(self.position(i), cell))
+            .collect();
+        Grid3::new_with_cells(cells, self.xs, self.ys, self.zs)
+    }
+
+    // The in-bounds cells sharing a face with (x, y, z)
+    pub fn neighbours6(&self, p: (usize, usize, usize)) -> Vec<(usize, usize, usize)> {
+        const OFFSETS: [(isize, isize, isize); 6] = [
+            (-1, 0, 0), (1, 0, 0),
+            (0, -1, 0), (0, 1, 0),
+            (0, 0, -1), (0, 0, 1),
+        ];
+        OFFSETS.iter().filter_map(|&d| self.step(p, d)).collect()
+    }
+
+    // The in-bounds cells sharing a face, edge or corner with (x, y, z)
+    pub fn neighbours26(&self, p: (usize, usize, usize)) -> Vec<(usize, usize, usize)> {
+        let mut neighbours = Vec::with_capacity(26);
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    if (dx, dy, dz) == (0, 0, 0) {
+                        continue;
+                    }
+                    if let Some(n) = self.step(p, (dx, dy, dz)) {
+                        neighbours.push(n);
+                    }
+                }
+            }
+        }
+        neighbours
+    }
+
+    fn step(&self, (x, y, z): (usize, usize, usize), (dx, dy, dz): (isize, isize, isize))
+        -> Option<(usize, usize, usize)>
+    {
+        let p = (
+            x.checked_add_signed(dx)?,
+            y.checked_add_signed(dy)?,
+            z.checked_add_signed(dz)?,
+        );
+        if self.contains(p) { Some(p) } else { None }
+    }
+}
+
+impl<T: Clone> Grid3<T> {
+    /*
+     * The 2D slice where `axis` equals `index`. The remaining axes keep their
+     * order, so a Z layer has y rows and x columns, a Y layer has z rows and
+     * x columns, and an X layer has z rows and y columns.
+     */
+    pub fn layer(&self, axis: Axis, index: usize) -> Grid<T> {
+        let (rows, cols) = match axis {
+            Axis::X => (self.zs, self.ys),
+            Axis::Y => (self.zs, self.xs),
+            Axis::Z => (self.ys, self.xs),
+        };
+        let mut cells = Vec::with_capacity(rows * cols);
+        for row in 0..rows {
+            for col in 0..cols {
+                let p = match axis {
+                    Axis::X => (index, col, row),
+                    Axis::Y => (col, index, row),
+                    Axis::Z => (col, row, index),
+                };
+                cells.push(self[p].clone());
+            }
+        }
+        Grid::new_with_cells(cells, rows, cols)
+    }
+}
+
+impl<T: Default> Grid3<T> {
+    pub fn new(xs: usize, ys: usize, zs: usize) -> Self {
+        let mut cells = Vec::new();
+        cells.resize_with(xs * ys * zs, T::default);
+        Grid3 { cells, xs, ys, zs }
+    }
+
+    /*
+     * An empty grid just large enough to hold every point. Also returns the
+     * minimum corner, which is subtracted from a point to find its cell.
+     */
+    pub fn bounding(points: &[Point3D<isize>]) -> (Self, Point3D<isize>) {
+        let Some(first) = points.first() else {
+            return (Self::new(0, 0, 0), Point3D::default());
+        };
+        let (mut min, mut max) = (*first, *first);
+        for p in points {
+            min = Point3D::new(min.x.min(p.x), min.y.min(p.y), min.z.min(p.z));
+            max = Point3D::new(max.x.max(p.x), max.y.max(p.y), max.z.max(p.z));
+        }
+        let grid = Self::new(
+            (max.x - min.x + 1) as usize,
+            (max.y - min.y + 1) as usize,
+            (max.z - min.z + 1) as usize,
+        );
+        (grid, min)
+    }
+}
+
+impl Grid3<bool> {
+    // Marks every point in a bounding grid, returning the grid's minimum corner
+    pub fn from_points(points: &[Point3D<isize>]) -> (Self, Point3D<isize>) {
+        let (mut grid, min) = Self::bounding(points);
+        for p in points {
+            let cell = ((p.x - min.x) as usize, (p.y - min.y) as usize, (p.z - min.z) as usize);
+            grid[cell] = true;
+        }
+        (grid, min)
+    }
+}
+
+impl<T> Index<(usize, usize, usize)> for Grid3<T> {
+    type Output = T;
+
+    fn index(&self, index: (usize, usize, usize)) -> &Self::Output {
+        &self.cells[self.offset(index)]
+    }
+}
+
+impl<T> IndexMut<(usize, usize, usize)> for Grid3<T> {
+    fn index_mut(&mut self, index: (usize, usize, usize)) -> &mut Self::Output {
+        let offset = self.offset(index);
+        &mut self.cells[offset]
+    }
+}
+
+impl<T> Index<Point3D<usize>> for Grid3<T> {
+    type Output = T;
+
+    fn index(&self, index: Point3D<usize>) -> &Self::Output {
+        &self[(index.x, index.y, index.z)]
+    }
+}
+
+impl<T> IndexMut<Point3D<usize>> for Grid3<T> {
+    fn index_mut(&mut self, index: Point3D<usize>) -> &mut Self::Output {
+        &mut self[(index.x, index.y, index.z)]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Axis, Grid3};
+    use crate::grid::Grid;
+    use crate::point::Point3D;
+
+    #[test]
+    pub fn test_layers() {
+        let grid = Grid3::new_with_cells((0..24).collect(), 4, 3, 2);
+        assert_eq!(17, grid[(1, 1, 1)]);
+        assert_eq!(23, grid[Point3D::new(3, 2, 1)]);
+        assert_eq!(Grid::new_with_cells((12..24).collect(), 3, 4), grid.layer(Axis::Z, 1));
+        assert_eq!(Grid::new_with_cells(vec![4, 5, 6, 7, 16, 17, 18, 19], 2, 4), grid.layer(Axis::Y, 1));
+        assert_eq!(Grid::new_with_cells(vec![0, 4, 8, 12, 16, 20], 2, 3), grid.layer(Axis::X, 0));
+    }
+
+    #[test]
+    pub fn test_neighbours() {
+        let grid: Grid3<u8> = Grid3::new(3, 3, 3);
+        assert_eq!(3, grid.neighbours6((0, 0, 0)).len());
+        assert_eq!(6, grid.neighbours6((1, 1, 1)).len());
+        assert_eq!(7, grid.neighbours26((0, 0, 0)).len());
+        assert_eq!(26, grid.neighbours26((1, 1, 1)).len());
+    }
+
+    #[test]
+    pub fn test_from_points() {
+        let points = [Point3D::new(-1, 2, 5), Point3D::new(1, 2, 3)];
+        let (grid, min) = Grid3::from_points(&points);
+        assert_eq!((3, 1, 3), grid.size());
+        assert_eq!(Point3D::new(-1, 2, 3), min);
+        assert!(grid[(0, 0, 2)] && grid[(2, 0, 0)] && !grid[(1, 0, 1)]);
+    }
+}
@@ -0,0 +1,220 @@
+use std::ops::{Add, Mul, Sub};
+use std::str::FromStr;
+
+use super::point::Point3D;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    InvalidDirection,
+}
+
+/*
+ * Flat-topped hexes have neighbours to the north and south, while
+ * pointy-topped hexes have them to the east and west. The two layouts agree
+ * on where NE and SW are but not on SE and NW.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Layout {
+    FlatTop,
+    PointyTop,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum HexDirection {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+impl HexDirection {
+    // The axial step in this direction, if the layout has such a neighbour
+    pub fn delta(&self, layout: Layout) -> Option<Hex> {
+        use HexDirection::*;
+        use Layout::*;
+        let (q, r) = match (*self, layout) {
+            (NorthEast, _) => (1, -1),
+            (SouthWest, _) => (-1, 1),
+            (North, FlatTop) => (0, -1),
+            (South, FlatTop) => (0, 1),
+            (SouthEast, FlatTop) => (1, 0),
+            (NorthWest, FlatTop) => (-1, 0),
+            (East, PointyTop) => (1, 0),
+            (West, PointyTop) => (-1, 0),
+            (SouthEast, PointyTop) => (0, 1),
+            (NorthWest, PointyTop) => (0, -1),
+            _ => return None,
+        };
+        Some(Hex::new(q, r))
+    }
+
+    /*
+     * Parses directions that are either separated by commas or whitespace, as
+     * in "ne,ne,s", or run together, as in "nwwswee".
+     */
+    pub fn parse_path(s: &str) -> Result<Vec<HexDirection>, ParseError> {
+        let mut dirs = Vec::new();
+        let mut rest = s.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        while !rest.is_empty() {
+            let len = match rest.get(..2) {
+                Some(pair) if pair.parse::<HexDirection>().is_ok() => 2,
+                _ => 1,
+            };
+            let dir = rest.get(..len).ok_or(ParseError::InvalidDirection)?.parse()?;
+            dirs.push(dir);
+            rest = rest[len..].trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        }
+        Ok(dirs)
+    }
+}
+
+impl FromStr for HexDirection {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use HexDirection::*;
+        match s.to_ascii_lowercase().as_str() {
+            "n" => Ok(North),
+            "ne" => Ok(NorthEast),
+            "e" => Ok(East),
+            "se" => Ok(SouthEast),
+            "s" => Ok(South),
+            "sw" => Ok(SouthWest),
+            "w" => Ok(West),
+            "nw" => Ok(NorthWest),
+            _ => Err(ParseError::InvalidDirection),
+        }
+    }
+}
+
+// Axial hex coordinates. The third cube coordinate is implied by q + r + s = 0.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Hex {
+    pub q: isize,
+    pub r: isize,
+}
+
+// The six axial neighbour offsets, in ring-walking order
+const AXIAL: [(isize, isize); 6] = [(1, 0), (1, -1), (0, -1), (-1, 0), (-1, 1), (0, 1)];
+
+impl Hex {
+    pub fn new(q: isize, r: isize) -> Self {
+        Self { q, r }
+    }
+
+    pub fn s(&self) -> isize {
+        -self.q - self.r
+    }
+
+    // Cube coordinates as (q, r, s)
+    pub fn to_cube(&self) -> Point3D<isize> {
+        Point3D::new(self.q, self.r, self.s())
+    }
+
+    pub fn from_cube(cube: Point3D<isize>) -> Option<Self> {
+        if cube.x + cube.y + cube.z == 0 {
+            Some(Self::new(cube.x, cube.y))
+        } else {
+            None
+        }
+    }
+
+    pub fn step(&self, dir: HexDirection, layout: Layout) -> Option<Hex> {
+        dir.delta(layout).map(|d| *self + d)
+    }
+
+    // Follows a path from this hex, failing if a direction doesn't exist in
+    // the layout
+    pub fn walk(&self, path: &[HexDirection], layout: Layout) -> Option<Hex> {
+        path.iter().try_fold(*self, |hex, &dir| hex.step(dir, layout))
+    }
+
+    pub fn neighbours(&self) -> [Hex; 6] {
+        AXIAL.map(|(q, r)| *self + Hex::new(q, r))
+    }
+
+    // Number of steps between hexes
+    pub fn distance(&self, other: &Hex) -> usize {
+        let d = *self - *other;
+        (d.q.unsigned_abs() + d.r.unsigned_abs() + d.s().unsigned_abs()) / 2
+    }
+
+    // Every hex exactly `radius` steps away
+    pub fn ring(&self, radius: usize) -> Vec<Hex> {
+        if radius == 0 {
+            return vec![*self];
+        }
+        let (q, r) = AXIAL[4];
+        let mut hex = *self + Hex::new(q, r) * radius as isize;
+        let mut ring = Vec::with_capacity(6 * radius);
+        for (q, r) in AXIAL {
+            for _ in 0..radius {
+                ring.push(hex);
+                hex = hex + Hex::new(q, r);
+            }
+        }
+        ring
+    }
+
+    // Every hex within `radius` steps, from the centre outwards ring by ring
+    pub fn spiral(&self, radius: usize) -> Vec<Hex> {
+        (0..=radius).flat_map(|r| self.ring(r)).collect()
+    }
+}
+
+impl Add for Hex {
+    type Output = Hex;
+
+    fn add(self, rhs: Hex) -> Self::Output {
+        Hex::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl Sub for Hex {
+    type Output = Hex;
+
+    fn sub(self, rhs: Hex) -> Self::Output {
+        Hex::new(self.q - rhs.q, self.r - rhs.r)
+    }
+}
+
+impl Mul<isize> for Hex {
+    type Output = Hex;
+
+    fn mul(self, rhs: isize) -> Self::Output {
+        Hex::new(self.q * rhs, self.r * rhs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Hex, HexDirection, Layout};
+
+    #[test]
+    pub fn test_flat_path() {
+        let path = HexDirection::parse_path("se,sw,se,sw,sw").unwrap();
+        let end = Hex::default().walk(&path, Layout::FlatTop).unwrap();
+        assert_eq!(3, end.distance(&Hex::default()));
+    }
+
+    #[test]
+    pub fn test_pointy_path() {
+        let path = HexDirection::parse_path("nwwswee").unwrap();
+        assert_eq!(5, path.len());
+        assert_eq!(Some(Hex::default()), Hex::default().walk(&path, Layout::PointyTop));
+        assert_eq!(None, Hex::default().walk(&[HexDirection::North], Layout::PointyTop));
+    }
+
+    #[test]
+    pub fn test_rings() {
+        let centre = Hex::new(2, -1);
+        assert_eq!(12, centre.ring(2).len());
+        assert!(centre.ring(2).iter().all(|h| h.distance(&centre) == 2));
+        assert_eq!(19, centre.spiral(2).len());
+        assert_eq!(Some(centre), Hex::from_cube(centre.to_cube()));
+    }
+}
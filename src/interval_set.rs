@@ -0,0 +1,203 @@
+use std::ops::{Add, Range, Sub};
+
+/*
+ * A set of integers stored as sorted half-open ranges. The ranges never
+ * overlap or touch, so every set has exactly one representation and equal
+ * sets compare equal. Empty ranges are ignored.
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    ranges: Vec<Range<T>>,
+}
+
+impl<T: Copy + Ord> Default for IntervalSet<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy + Ord> IntervalSet<T> {
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    // Sorts and merges arbitrary ranges into the canonical form
+    fn coalesce(mut ranges: Vec<Range<T>>) -> Self {
+        ranges.retain(|r| r.start < r.end);
+        ranges.sort_unstable_by_key(|r| r.start);
+        let mut merged: Vec<Range<T>> = Vec::with_capacity(ranges.len());
+        for range in ranges {
+            match merged.last_mut() {
+                Some(last) if range.start <= last.end => last.end = last.end.max(range.end),
+                _ => merged.push(range),
+            }
+        }
+        Self { ranges: merged }
+    }
+
+    pub fn insert(&mut self, range: Range<T>) {
+        if range.start >= range.end {
+            return;
+        }
+        // Ranges from i up to j overlap or touch the new one
+        let i = self.ranges.partition_point(|r| r.end < range.start);
+        let j = self.ranges.partition_point(|r| r.start <= range.end);
+        let merged = if i < j {
+            self.ranges[i].start.min(range.start)..self.ranges[j - 1].end.max(range.end)
+        } else {
+            range
+        };
+        self.ranges.splice(i..j, [merged]);
+    }
+
+    pub fn remove(&mut self, range: Range<T>) {
+        *self = self.difference(&Self::coalesce(vec![range]));
+    }
+
+    pub fn contains(&self, n: &T) -> bool {
+        let i = self.ranges.partition_point(|r| r.end <= *n);
+        self.ranges.get(i).is_some_and(|r| r.contains(n))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    // The disjoint ranges in increasing order
+    pub fn iter(&self) -> std::slice::Iter<'_, Range<T>> {
+        self.ranges.iter()
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        Self::coalesce(self.ranges.iter().chain(other.ranges.iter()).cloned().collect())
+    }
+
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a, b) = (&self.ranges[i], &other.ranges[j]);
+            let start = a.start.max(b.start);
+            let end = a.end.min(b.end);
+            if start < end {
+                ranges.push(start..end);
+            }
+            // Whichever ends first can't meet anything further on
+            if a.end < b.end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        Self { ranges }
+    }
+
+    pub fn difference(&self, other: &Self) -> Self {
+        let mut ranges = Vec::new();
+        let mut j = 0;
+        for a in &self.ranges {
+            let mut start = a.start;
+            // Skip the ranges of other that end before this one starts
+            while j < other.ranges.len() && other.ranges[j].end <= start {
+                j += 1;
+            }
+            let mut k = j;
+            while k < other.ranges.len() && other.ranges[k].start < a.end {
+                let b = &other.ranges[k];
+                if start < b.start {
+                    ranges.push(start..b.start);
+                }
+                start = start.max(b.end);
+                k += 1;
+            }
+            if start < a.end {
+                ranges.push(start..a.end);
+            }
+        }
+        Self { ranges }
+    }
+
+    // Everything within bounds that isn't in the set
+    pub fn complement(&self, bounds: Range<T>) -> Self {
+        Self::coalesce(vec![bounds]).difference(self)
+    }
+}
+
+impl<T> IntervalSet<T>
+where
+    T: Copy + Ord + Default + Add<Output = T> + Sub<Output = T>,
+{
+    // How many numbers the set holds
+    pub fn total_len(&self) -> T {
+        self.ranges.iter().fold(T::default(), |sum, r| sum + (r.end - r.start))
+    }
+}
+
+impl<T: Copy + Ord> FromIterator<Range<T>> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = Range<T>>>(iter: I) -> Self {
+        Self::coalesce(iter.into_iter().collect())
+    }
+}
+
+impl<T: Copy + Ord> From<Range<T>> for IntervalSet<T> {
+    fn from(range: Range<T>) -> Self {
+        Self::coalesce(vec![range])
+    }
+}
+
+impl<'a, T> IntoIterator for &'a IntervalSet<T> {
+    type Item = &'a Range<T>;
+    type IntoIter = std::slice::Iter<'a, Range<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.iter()
+    }
+}
+
+impl<T> IntoIterator for IntervalSet<T> {
+    type Item = Range<T>;
+    type IntoIter = std::vec::IntoIter<Range<T>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ranges.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::IntervalSet;
+
+    #[test]
+    pub fn test_insert() {
+        let mut set = IntervalSet::new();
+        set.insert(10..20);
+        set.insert(30..40);
+        set.insert(20..25);
+        set.insert(5..5);
+        assert_eq!(vec![10..25, 30..40], set.iter().cloned().collect::<Vec<_>>());
+        set.insert(-5..35);
+        assert_eq!(vec![-5..40], set.clone().into_iter().collect::<Vec<_>>());
+        set.remove(0..10);
+        assert_eq!(IntervalSet::from_iter([-5..0, 10..40]), set);
+        assert_eq!(35, set.total_len());
+        assert!(set.contains(&-5) && !set.contains(&0) && set.contains(&39) && !set.contains(&40));
+    }
+
+    #[test]
+    pub fn test_set_algebra() {
+        let a: IntervalSet<i64> = [-10..-2, 0..5, 8..12, 20..30].into_iter().collect();
+        let b: IntervalSet<i64> = [-4..1, 4..9, 11..25].into_iter().collect();
+        let bounds = -15..35;
+        let members = |set: &IntervalSet<i64>| bounds.clone().filter(|n| set.contains(n)).collect::<Vec<_>>();
+        let expect = |f: &dyn Fn(bool, bool) -> bool| {
+            bounds.clone().filter(|n| f(a.contains(n), b.contains(n))).collect::<Vec<_>>()
+        };
+        assert_eq!(expect(&|x, y| x || y), members(&a.union(&b)));
+        assert_eq!(expect(&|x, y| x && y), members(&a.intersection(&b)));
+        assert_eq!(expect(&|x, y| x && !y), members(&a.difference(&b)));
+        assert_eq!(expect(&|x, _| !x), members(&a.complement(bounds.clone())));
+        assert_eq!(a.union(&b).total_len(), a.total_len() + b.total_len() - a.intersection(&b).total_len());
+        assert_eq!(IntervalSet::from(-10..30), a.union(&a.complement(-10..30)));
+        assert!(a.intersection(&a.complement(-10..30)).is_empty());
+    }
+}
@@ -2,15 +2,30 @@ use std::env;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 
+pub mod automaton;
+pub mod bit_grid;
 pub mod camel_cards;
 pub mod cube_bag;
+pub mod cycle;
+pub mod direction;
 pub mod grid;
+pub mod grid3;
+pub mod hex;
+pub mod interval_set;
+pub mod line;
 pub mod map_route;
 pub mod oasis;
 pub mod pipes;
 pub mod point;
+pub mod polygon;
 pub mod race;
+pub mod rational;
 pub mod range_map;
+pub mod rect;
+pub mod rotation;
+pub mod schematic;
+pub mod spatial;
+pub mod summed_area;
 
 pub fn input_arg() -> String {
     env::args()
@@ -90,18 +105,6 @@ pub fn read_uint_grid(path: &str) -> grid::Grid<usize> {
 }
 
 pub fn read_char_grid(path: &str) -> grid::Grid<char> {
-    let mut rows = 0;
-    let mut cols = 0;
-    let mut cells: Vec<char> = Vec::new();
-    read_lines(path).for_each(|line| {
-        rows += 1;
-        let mut cs: Vec<char> = line.chars().collect();
-        if cols == 0 {
-            cols = cs.len();
-        } else if cols != cs.len() {
-            panic!("inconsistent grid width");
-        }
-        cells.append(&mut cs);
-    });
-    grid::Grid::new_with_cells(cells, rows, cols)
+    let lines: Vec<String> = read_lines(path).collect();
+    grid::Grid::from_lines(&lines)
 }
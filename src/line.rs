@@ -0,0 +1,265 @@
+use super::point::{Point2D, Point3D};
+use super::rational::Rational;
+
+pub type Point2Q = Point2D<Rational>;
+pub type Point3Q = Point3D<Rational>;
+
+// Which values of the parameter t in origin + t * dir belong to a line
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Extent {
+    // Any t
+    Line,
+    // t >= 0
+    Ray,
+    // 0 <= t <= 1
+    Segment,
+}
+
+impl Extent {
+    fn bounds(&self) -> (Option<Rational>, Option<Rational>) {
+        match *self {
+            Extent::Line => (None, None),
+            Extent::Ray => (Some(Rational::ZERO), None),
+            Extent::Segment => (Some(Rational::ZERO), Some(Rational::ONE)),
+        }
+    }
+
+    fn contains(&self, t: Rational) -> bool {
+        let (lo, hi) = self.bounds();
+        lo.is_none_or(|lo| t >= lo) && hi.is_none_or(|hi| t <= hi)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Intersection<P> {
+    None,
+    Point(P),
+    // Collinear and sharing more than one point
+    Overlap,
+}
+
+impl<P> Intersection<P> {
+    fn map<Q, F: FnOnce(P) -> Q>(self, f: F) -> Intersection<Q> {
+        match self {
+            Intersection::None => Intersection::None,
+            Intersection::Point(p) => Intersection::Point(f(p)),
+            Intersection::Overlap => Intersection::Overlap,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Line2 {
+    pub origin: Point2Q,
+    pub dir: Point2Q,
+    pub extent: Extent,
+}
+
+impl Line2 {
+    pub fn line(origin: Point2Q, dir: Point2Q) -> Self {
+        Self { origin, dir, extent: Extent::Line }
+    }
+
+    pub fn ray(origin: Point2Q, dir: Point2Q) -> Self {
+        Self { origin, dir, extent: Extent::Ray }
+    }
+
+    pub fn segment(start: Point2Q, end: Point2Q) -> Self {
+        Self { origin: start, dir: end - start, extent: Extent::Segment }
+    }
+
+    pub fn at(&self, t: Rational) -> Point2Q {
+        self.origin + self.dir * t
+    }
+
+    fn lift(&self) -> Line3 {
+        let lift = |p: Point2Q| Point3D::new(p.x, p.y, Rational::ZERO);
+        Line3 { origin: lift(self.origin), dir: lift(self.dir), extent: self.extent }
+    }
+
+    pub fn contains(&self, p: &Point2Q) -> bool {
+        self.lift().contains(&Point3D::new(p.x, p.y, Rational::ZERO))
+    }
+
+    pub fn intersect(&self, other: &Line2) -> Intersection<Point2Q> {
+        // A 2D intersection is a 3D one that happens to lie in the z = 0 plane
+        self.lift().intersect(&other.lift()).map(|p| Point2D::new(p.x, p.y))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct Line3 {
+    pub origin: Point3Q,
+    pub dir: Point3Q,
+    pub extent: Extent,
+}
+
+impl Line3 {
+    pub fn line(origin: Point3Q, dir: Point3Q) -> Self {
+        Self { origin, dir, extent: Extent::Line }
+    }
+
+    pub fn ray(origin: Point3Q, dir: Point3Q) -> Self {
+        Self { origin, dir, extent: Extent::Ray }
+    }
+
+    pub fn segment(start: Point3Q, end: Point3Q) -> Self {
+        Self { origin: start, dir: end - start, extent: Extent::Segment }
+    }
+
+    pub fn at(&self, t: Rational) -> Point3Q {
+        self.origin + self.dir * t
+    }
+
+    pub fn contains(&self, p: &Point3Q) -> bool {
+        let offset = *p - self.origin;
+        let len = self.dir.length_squared();
+        if len == Rational::ZERO {
+            // A zero direction leaves just the origin, whatever the extent
+            return offset.length_squared() == Rational::ZERO;
+        }
+        offset.cross(&self.dir).length_squared() == Rational::ZERO
+            && self.extent.contains(offset.dot(&self.dir) / len)
+    }
+
+    pub fn intersect(&self, other: &Line3) -> Intersection<Point3Q> {
+        let offset = other.origin - self.origin;
+        let normal = self.dir.cross(&other.dir);
+        let denom = normal.length_squared();
+        if denom == Rational::ZERO {
+            return self.intersect_parallel(other);
+        }
+        if offset.dot(&normal) != Rational::ZERO {
+            // Skew lines never meet
+            return Intersection::None;
+        }
+        let t = offset.cross(&other.dir).dot(&normal) / denom;
+        let u = offset.cross(&self.dir).dot(&normal) / denom;
+        if self.extent.contains(t) && other.extent.contains(u) {
+            Intersection::Point(self.at(t))
+        } else {
+            Intersection::None
+        }
+    }
+
+    fn intersect_parallel(&self, other: &Line3) -> Intersection<Point3Q> {
+        // A zero direction leaves a single point, which meets the other line
+        // only if the other line contains it
+        let meets = |line: &Line3, p: Point3Q| {
+            if line.contains(&p) { Intersection::Point(p) } else { Intersection::None }
+        };
+        if self.dir.length_squared() == Rational::ZERO {
+            return meets(other, self.origin);
+        }
+        if other.dir.length_squared() == Rational::ZERO {
+            return meets(self, other.origin);
+        }
+        let zero = Point3D::new(Rational::ZERO, Rational::ZERO, Rational::ZERO);
+        let offset = other.origin - self.origin;
+        if offset.cross(&self.dir) != zero {
+            return Intersection::None;
+        }
+        // Collinear, so find where other's extent lies along self's parameter
+        let len = self.dir.length_squared();
+        let start = offset.dot(&self.dir) / len;
+        let step = other.dir.dot(&self.dir) / len;
+        let to_self = |u: Option<Rational>| u.map(|u| start + step * u);
+        let (a, b) = other.extent.bounds();
+        let (a, b) = if step < Rational::ZERO { (to_self(b), to_self(a)) } else { (to_self(a), to_self(b)) };
+        let (lo, hi) = self.extent.bounds();
+        let lo = match (lo, a) {
+            (Some(x), Some(y)) => Some(x.max(y)),
+            (x, y) => x.or(y),
+        };
+        let hi = match (hi, b) {
+            (Some(x), Some(y)) => Some(x.min(y)),
+            (x, y) => x.or(y),
+        };
+        match (lo, hi) {
+            (Some(lo), Some(hi)) if lo > hi => Intersection::None,
+            (Some(lo), Some(hi)) if lo == hi => Intersection::Point(self.at(lo)),
+            _ => Intersection::Overlap,
+        }
+    }
+}
+
+impl From<Point2D<i64>> for Point2Q {
+    fn from(p: Point2D<i64>) -> Self {
+        Point2D::new(p.x.into(), p.y.into())
+    }
+}
+
+impl From<Point3D<i64>> for Point3Q {
+    fn from(p: Point3D<i64>) -> Self {
+        Point3D::new(p.x.into(), p.y.into(), p.z.into())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Intersection, Line2, Line3, Point2Q};
+    use crate::point::{Point2D, Point3D};
+    use crate::rational::Rational;
+
+    #[test]
+    pub fn test_hailstones() {
+        let stones = [
+            ((19, 13), (-2, 1)),
+            ((18, 19), (-1, -1)),
+            ((20, 25), (-2, -2)),
+            ((12, 31), (-1, -2)),
+            ((20, 19), (1, -5)),
+        ];
+        let rays: Vec<Line2> = stones.iter()
+            .map(|&((x, y), (dx, dy))| {
+                Line2::ray(Point2D::new(x, y).into(), Point2D::new(dx, dy).into())
+            })
+            .collect();
+        let (lo, hi) = (Rational::from(7i64), Rational::from(27i64));
+        let mut count = 0;
+        for (i, a) in rays.iter().enumerate() {
+            for b in rays[i + 1..].iter() {
+                if let Intersection::Point(p) = a.intersect(b) {
+                    if p.x >= lo && p.x <= hi && p.y >= lo && p.y <= hi {
+                        count += 1;
+                    }
+                }
+            }
+        }
+        assert_eq!(2, count);
+    }
+
+    #[test]
+    pub fn test_collinear_segments() {
+        let p = |x: i64, y: i64| -> Point2Q { Point2D::new(x, y).into() };
+        let a = Line2::segment(p(0, 0), p(4, 4));
+        assert_eq!(Intersection::Overlap, a.intersect(&Line2::segment(p(6, 6), p(2, 2))));
+        assert_eq!(Intersection::Point(p(4, 4)), a.intersect(&Line2::segment(p(6, 6), p(4, 4))));
+        assert_eq!(Intersection::None, a.intersect(&Line2::segment(p(5, 5), p(6, 6))));
+        assert_eq!(Intersection::None, a.intersect(&Line2::segment(p(0, 1), p(4, 5))));
+        let half = Rational::new(1, 2);
+        assert_eq!(Intersection::Point(Point2D::new(half, half)), a.intersect(&Line2::line(p(0, 1), p(1, -1))));
+    }
+
+    #[test]
+    pub fn test_zero_direction() {
+        let p = |x: i64, y: i64| -> Point2Q { Point2D::new(x, y).into() };
+        let a = Line2::segment(p(0, 0), p(4, 4));
+        let dot = Line2::segment(p(1, 1), p(1, 1));
+        assert_eq!(Intersection::Point(p(1, 1)), dot.intersect(&a));
+        assert_eq!(Intersection::Point(p(1, 1)), a.intersect(&dot));
+        assert_eq!(Intersection::None, Line2::segment(p(5, 5), p(5, 5)).intersect(&a));
+        assert_eq!(Intersection::None, Line2::ray(p(1, 2), p(0, 0)).intersect(&a));
+        assert_eq!(Intersection::Point(p(1, 1)), dot.intersect(&Line2::ray(p(1, 1), p(0, 0))));
+        assert_eq!(Intersection::None, dot.intersect(&Line2::ray(p(2, 1), p(0, 0))));
+        assert!(a.contains(&p(4, 4)) && !a.contains(&p(5, 5)));
+    }
+
+    #[test]
+    pub fn test_skew_lines() {
+        let p = |x: i64, y: i64, z: i64| Point3D::new(x, y, z).into();
+        let a = Line3::line(p(0, 0, 0), p(1, 0, 0));
+        assert_eq!(Intersection::None, a.intersect(&Line3::line(p(0, 0, 1), p(0, 1, 0))));
+        assert_eq!(Intersection::Point(p(3, 0, 0)), a.intersect(&Line3::ray(p(3, -2, 2), p(0, 1, -1))));
+    }
+}
@@ -1,13 +1,19 @@
-use std::collections::{HashMap, HashSet};
-use std::iter::Cycle;
-use std::str::Chars;
+use std::collections::HashMap;
 
 use regex::Regex;
 
+use super::cycle;
+
 pub struct Routes {
     nodes: HashMap<String, (String, String)>,
 }
 
+impl Default for Routes {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Routes {
     pub fn new() -> Self {
         Self { nodes: HashMap::new() }
@@ -54,43 +60,38 @@ impl Routes {
 
 pub struct Path <'a> {
     routes: &'a Routes,
-    path: Cycle<Chars<'a>>,
-    path_len: usize,
+    path: &'a [u8],
+    offset: usize,
     position: &'a str,
 }
 
 impl <'a> Path <'a> {
     pub fn new(routes: &'a Routes, path: &'a str, start: &'a str) -> Self {
-        Self { routes, path: path.chars().cycle(), path_len: path.len(), position: start }
+        Self { routes, path: path.as_bytes(), offset: 0, position: start }
     }
 
-    pub fn step(&mut self) -> &str {
-        let c = self.path.next().unwrap();
-        let (left, right) = self.routes.get(self.position)
+    fn next(&self, offset: usize, position: &str) -> (usize, &'a str) {
+        let (left, right) = self.routes.get(position)
             .expect("Nowhere to go");
-        self.position = if c == 'L' { left } else { right };
+        let position = if self.path[offset] == b'L' { left } else { right };
+        ((offset + 1) % self.path.len(), position)
+    }
+
+    pub fn step(&mut self) -> &str {
+        (self.offset, self.position) = self.next(self.offset, self.position);
         self.position
     }
 
-    pub fn steps_to_z(&mut self) -> Vec<usize> {
+    pub fn steps_to_z(&self) -> Vec<usize> {
         // Find all distances to end nodes until a loop is detected, where a loop
-        // is defined as reaching the same node at the same offset into the path
-        let mut zs = Vec::new();
-        let mut steps = 0;
-        let mut seen: HashSet<(usize, String)> = HashSet::new();
-        let path_len = self.path_len;
-        seen.insert((0, self.position.to_owned()));
-        loop {
-            let position = self.step().to_owned();
-            steps += 1;
-            if position.ends_with("Z") {
-                zs.push(steps);
-            }
-            let offset = steps % path_len;
-            if !seen.insert((offset, position)) {
-                break;
-            }
-        }
-        zs
+        // is defined as reaching the same node at the same offset into the path.
+        // Distances are counted from the current position, which is left as is.
+        let initial = (self.offset, self.position);
+        let (loop_at, states) = cycle::hashed(initial, |&(offset, position)| {
+            self.next(offset, position)
+        });
+        (1..=loop_at.start + loop_at.len)
+            .filter(|&steps| states[loop_at.offset(steps)].1.ends_with('Z'))
+            .collect()
     }
 }
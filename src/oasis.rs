@@ -14,7 +14,7 @@ impl Memo {
         self.right = value;
         let left = self.left?;
         let diff = value - left;
-        self.sum += diff.abs() as usize;
+        self.sum += diff.unsigned_abs();
         if diff != 0 {
             Some(diff)
         } else {
@@ -1,22 +1,17 @@
 use std::collections::{HashMap, VecDeque};
 use std::ops::Index;
 
+use super::direction::Direction;
 use super::grid;
 use super::grid::Grid;
+use super::point::Point2D;
+use super::polygon::Polygon;
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ParseError {
     InvalidChar,
 }
 
-#[derive(Debug, Copy, Clone, PartialEq, Eq)]
-pub enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Pipe {
     NS, // |
@@ -69,6 +64,48 @@ impl Pipe {
         }
     }
 
+    // The sides of the cell the pipe connects to
+    pub fn connections(&self) -> Vec<Direction> {
+        use Direction::{North, East, South, West};
+        use Pipe::*;
+        match *self {
+            NS => vec![North, South],
+            EW => vec![East, West],
+            NE => vec![North, East],
+            NW => vec![North, West],
+            SW => vec![South, West],
+            SE => vec![South, East],
+            // Start's connections depend on its neighbours
+            _ => vec![],
+        }
+    }
+
+    pub fn from_connections(a: Direction, b: Direction) -> Option<Pipe> {
+        [Pipe::NS, Pipe::EW, Pipe::NE, Pipe::NW, Pipe::SW, Pipe::SE]
+            .into_iter()
+            .find(|pipe| {
+                let connections = pipe.connections();
+                a != b && connections.contains(&a) && connections.contains(&b)
+            })
+    }
+
+    // The pipe drawn as a 3x3 block, with true wherever the pipe runs
+    pub fn glyph(&self) -> Grid<bool> {
+        let mut glyph = Grid::new(3, 3);
+        let connections = self.connections();
+        glyph[(1, 1)] = !connections.is_empty();
+        for dir in connections {
+            let cell = match dir {
+                Direction::North => (0, 1),
+                Direction::East => (1, 2),
+                Direction::South => (2, 1),
+                Direction::West => (1, 0),
+            };
+            glyph[cell] = true;
+        }
+        glyph
+    }
+
     pub fn to_char(&self) -> char {
         use Pipe::*;
         match *self {
@@ -121,58 +158,148 @@ impl Matrix {
     }
 
     pub fn start(&self) -> (usize, usize) {
-        let (row, col, _) = self.grid.iter()
-            .find(|(_, _, &pipe)| pipe == Pipe::Start)
-            .expect("No start position found");
-        (row, col)
+        self.grid.position_of(&Pipe::Start)
+            .expect("No start position found")
+    }
+
+    // The pipe hidden by the start, based on which neighbours connect to it
+    pub fn start_pipe(&self) -> Pipe {
+        use Direction::{North, East, South, West};
+        let (rows, cols) = self.size();
+        let (row, col) = self.start();
+        let connects = |row: usize, col: usize, dir: Direction| {
+            row < rows && col < cols && self.grid[(row, col)].connections().contains(&dir)
+        };
+        let mut dirs = Vec::new();
+        if row > 0 && connects(row - 1, col, South) {
+            dirs.push(North);
+        }
+        if connects(row, col + 1, West) {
+            dirs.push(East);
+        }
+        if connects(row + 1, col, North) {
+            dirs.push(South);
+        }
+        if col > 0 && connects(row, col - 1, East) {
+            dirs.push(West);
+        }
+        match dirs[..] {
+            [a, b] => Pipe::from_connections(a, b).expect("Start must connect two sides"),
+            _ => panic!("Start must connect exactly two neighbours"),
+        }
     }
 
     // (row, col, dist)
     pub fn furthest(&mut self) -> (usize, usize, usize) {
-        let (rows, cols) = self.size();
+        let size = self.size();
         // (row, col, outbound direction, distance)
         let mut queue: VecDeque<(usize, usize, Direction, usize)> = VecDeque::new();
-        let (row, col) = self.start();
-        if row > 0 {
-            queue.push_back((row - 1, col, Direction::North, 0));
-        }
-        queue.push_back((row + 1, col, Direction::South, 0));
-        if col > 0 {
-            queue.push_back((row, col - 1, Direction::West, 0));
+        let start = self.start();
+        for dir in Direction::all() {
+            if let Some((row, col)) = dir.step(start, size) {
+                queue.push_back((row, col, dir, 0));
+            }
         }
-        queue.push_back((row, col + 1, Direction::East, 0));
-        self.visited.insert((row, col), 0);
-        let row_range = 0..rows;
-        let col_range = 0..cols;
+        self.visited.insert(start, 0);
         while let Some((row, col, dir, dist)) = queue.pop_front() {
-            if !row_range.contains(&row) || !col_range.contains(&col) {
-                continue;
-            } else if self.visited.contains_key(&(row, col)) {
+            if self.visited.contains_key(&(row, col)) {
                 continue;
             }
             let pipe = self.grid[(row, col)];
             if let Some(out_dir) = pipe.exit(dir) {
-                match out_dir {
-                    Direction::North => if row > 0 { queue.push_back((row - 1, col, out_dir, dist + 1)) }
-                    Direction::East => queue.push_back((row, col + 1, out_dir, dist + 1)),
-                    Direction::South => queue.push_back((row + 1, col, out_dir, dist + 1)),
-                    Direction::West => if col > 0 { queue.push_back((row, col - 1, out_dir, dist + 1)) },
+                if let Some((row, col)) = out_dir.step((row, col), size) {
+                    queue.push_back((row, col, out_dir, dist + 1));
                 }
                 self.visited.insert((row, col), dist + 1);
             }
         }
         let max = self.visited.iter().max_by_key(|(_, &dist)| dist)
             .expect("No nodes visited");
-        ((*max.0).0, (*max.0).1, *max.1)
+        (max.0.0, max.0.1, *max.1)
     }
 
-    pub fn iter(&self) -> MatrixIter {
+    pub fn iter(&self) -> MatrixIter<'_> {
         MatrixIter { iter: self.grid.iter() }
     }
 
     pub fn visited(&self, row: usize, col: usize) -> bool {
         self.visited.contains_key(&(row, col))
     }
+
+    // The (row, col) of every pipe in the loop, in order, starting from S
+    pub fn loop_path(&self) -> Vec<(usize, usize)> {
+        let size = self.size();
+        let start = self.start();
+        let mut dir = self.start_pipe().connections()[0];
+        let mut pos = start;
+        let mut path = vec![start];
+        loop {
+            pos = dir.step(pos, size).expect("Loop leaves the grid");
+            if pos == start {
+                return path;
+            }
+            path.push(pos);
+            dir = self.grid[pos].exit(dir).expect("Loop is broken");
+        }
+    }
+
+    // The loop as a polygon with x as the column and y as the row
+    pub fn loop_polygon(&self) -> Polygon {
+        let vertices = self.loop_path().into_iter()
+            .map(|(row, col)| Point2D::new(col as i64, row as i64))
+            .collect();
+        Polygon::new(vertices)
+    }
+
+    // Each visited pipe as its 3x3 glyph, with everything else left empty
+    pub fn upscaled(&self) -> Grid<bool> {
+        let start = self.start_pipe();
+        self.grid.upscale(3, |(row, col), pipe| {
+            if !self.visited(row, col) {
+                Grid::new(3, 3)
+            } else if *pipe == Pipe::Start {
+                start.glyph()
+            } else {
+                pipe.glyph()
+            }
+        })
+    }
+
+    /*
+     * Counts the cells enclosed by the loop found by furthest(). Upscaling
+     * opens gaps between adjacent pipes, so flooding the upscaled grid from
+     * its edges reaches every cell outside the loop.
+     */
+    pub fn enclosed_area(&self) -> usize {
+        let walls = self.upscaled();
+        let (rows, cols) = walls.size();
+        let mut outside: Grid<bool> = Grid::new(rows, cols);
+        let mut queue: VecDeque<(usize, usize)> = walls.positions()
+            .filter(|&(row, col)| row == 0 || col == 0 || row == rows - 1 || col == cols - 1)
+            .filter(|&p| !walls[p])
+            .collect();
+        for &p in queue.iter() {
+            outside[p] = true;
+        }
+        while let Some((row, col)) = queue.pop_front() {
+            let neighbours = [
+                (row.wrapping_sub(1), col),
+                (row + 1, col),
+                (row, col.wrapping_sub(1)),
+                (row, col + 1),
+            ];
+            for (row, col) in neighbours {
+                if row < rows && col < cols && !walls[(row, col)] && !outside[(row, col)] {
+                    outside[(row, col)] = true;
+                    queue.push_back((row, col));
+                }
+            }
+        }
+        let inside = outside.downsample(3, |(row, col), block| {
+            !*block[4] && !self.visited(row, col)
+        });
+        inside.iter().filter(|(_, _, &inside)| inside).count()
+    }
 }
 
 impl Index<(usize, usize)> for Matrix {
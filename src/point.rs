@@ -1,4 +1,6 @@
+use std::fmt;
 use std::num::ParseIntError;
+use std::ops::{Add, AddAssign, Div, DivAssign, Index, IndexMut, Mul, MulAssign, Neg, Sub, SubAssign};
 use std::str::FromStr;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -7,6 +9,50 @@ pub enum ParsePointError {
     BadCoord(ParseIntError),
 }
 
+/*
+ * Parses exactly N coordinates split by `sep`, or by any whitespace if `sep`
+ * is blank. Whitespace, a surrounding pair of brackets and labels such as
+ * "x=" are all ignored, so "<1, 2>" and "x=1, y=2" both parse. A missing
+ * coordinate reports how many were found and extra coordinates report the
+ * total count.
+ */
+fn parse_coords<T, const N: usize>(s: &str, sep: &str) -> Result<[T; N], ParsePointError>
+where
+    T: FromStr<Err = ParseIntError>,
+{
+    let s = strip_brackets(s.trim());
+    let parts: Vec<&str> = if sep.trim().is_empty() {
+        s.split_whitespace().collect()
+    } else {
+        s.split(sep).collect()
+    };
+    let mut coords = Vec::with_capacity(N);
+    for i in 0..N {
+        let part = parts.get(i).ok_or(ParsePointError::WrongDimensions(i))?;
+        coords.push(strip_label(part.trim()).parse::<T>().map_err(ParsePointError::BadCoord)?);
+    }
+    if parts.len() > N {
+        return Err(ParsePointError::WrongDimensions(parts.len()));
+    }
+    Ok(coords.try_into().unwrap_or_else(|_| unreachable!()))
+}
+
+fn strip_brackets(s: &str) -> &str {
+    for (open, close) in [('<', '>'), ('(', ')'), ('[', ']'), ('{', '}')] {
+        if let Some(inner) = s.strip_prefix(open).and_then(|s| s.strip_suffix(close)) {
+            return inner.trim();
+        }
+    }
+    s
+}
+
+fn strip_label(s: &str) -> &str {
+    match s.split_once('=') {
+        Some((label, n)) if label.trim().chars().all(char::is_alphabetic) => n.trim(),
+        _ => s,
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Point2D<T> {
     pub x: T,
@@ -19,6 +65,39 @@ impl<T> Point2D<T> {
     }
 }
 
+impl<T> Point2D<T>
+where
+    T: FromStr<Err = ParseIntError>,
+{
+    // Like from_str, but with coordinates split by `sep` instead of commas
+    pub fn parse_with(s: &str, sep: &str) -> Result<Self, ParsePointError> {
+        let [x, y] = parse_coords(s, sep)?;
+        Ok(Self { x, y })
+    }
+}
+
+/*
+ * Grids are indexed by (row, col) while points are (x, y), so a column is an
+ * x coordinate and a row is a y coordinate. These make the swap explicit.
+ */
+impl Point2D<isize> {
+    // The point for a (row, col) index, if both fit in an isize
+    pub fn from_row_col((row, col): (usize, usize)) -> Option<Self> {
+        Some(Self { x: isize::try_from(col).ok()?, y: isize::try_from(row).ok()? })
+    }
+
+    // The (row, col) index for this point, if neither coordinate is negative
+    pub fn to_row_col(&self) -> Option<(usize, usize)> {
+        Some((usize::try_from(self.y).ok()?, usize::try_from(self.x).ok()?))
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point2D<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
 impl<T> Default for Point2D<T>
 where
     T: Default,
@@ -35,15 +114,8 @@ where
     type Err = ParsePointError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut ns = s
-            .split(',')
-            .map(|n| n.parse::<T>().map_err(Self::Err::BadCoord));
-        let x = ns.next().unwrap_or(Err(Self::Err::WrongDimensions(0)))?;
-        let y = ns.next().unwrap_or(Err(Self::Err::WrongDimensions(1)))?;
-        match ns.count() {
-            0 => Ok(Self { x, y }),
-            n => Err(Self::Err::WrongDimensions(n + 2)),
-        }
+        let [x, y] = parse_coords(s, ",")?;
+        Ok(Self { x, y })
     }
 }
 
@@ -60,6 +132,46 @@ impl<T> Point3D<T> {
     }
 }
 
+impl<T> Point3D<T>
+where
+    T: FromStr<Err = ParseIntError>,
+{
+    // Like from_str, but with coordinates split by `sep` instead of commas
+    pub fn parse_with(s: &str, sep: &str) -> Result<Self, ParsePointError> {
+        let [x, y, z] = parse_coords(s, sep)?;
+        Ok(Self { x, y, z })
+    }
+}
+
+// Vector algebra, treating the point as an offset from the origin
+impl<T> Point3D<T>
+where
+    T: Copy + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+{
+    pub fn dot(&self, other: &Self) -> T {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    // Perpendicular to both, following the right-hand rule
+    pub fn cross(&self, other: &Self) -> Self {
+        Self::new(
+            self.y * other.z - self.z * other.y,
+            self.z * other.x - self.x * other.z,
+            self.x * other.y - self.y * other.x,
+        )
+    }
+
+    pub fn length_squared(&self) -> T {
+        self.dot(self)
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Point3D<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
 impl<T> Default for Point3D<T>
 where
     T: Default,
@@ -76,15 +188,455 @@ where
     type Err = ParsePointError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut ns = s
-            .split(',')
-            .map(|n| n.parse::<T>().map_err(Self::Err::BadCoord));
-        let x = ns.next().unwrap_or(Err(Self::Err::WrongDimensions(0)))?;
-        let y = ns.next().unwrap_or(Err(Self::Err::WrongDimensions(1)))?;
-        let z = ns.next().unwrap_or(Err(Self::Err::WrongDimensions(2)))?;
-        match ns.count() {
-            0 => Ok(Self { x, y, z }),
-            n => Err(Self::Err::WrongDimensions(n + 3)),
+        let [x, y, z] = parse_coords(s, ",")?;
+        Ok(Self { x, y, z })
+    }
+}
+
+// A point with any number of dimensions, for when 2D and 3D aren't enough
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Point<T, const N: usize> {
+    pub coords: [T; N],
+}
+
+impl<T, const N: usize> Point<T, N> {
+    pub fn new(coords: [T; N]) -> Self {
+        Self { coords }
+    }
+}
+
+impl<T: Default, const N: usize> Default for Point<T, N> {
+    fn default() -> Self {
+        Self::new(std::array::from_fn(|_| T::default()))
+    }
+}
+
+impl<T, const N: usize> Point<T, N>
+where
+    T: FromStr<Err = ParseIntError>,
+{
+    // Like from_str, but with coordinates split by `sep` instead of commas
+    pub fn parse_with(s: &str, sep: &str) -> Result<Self, ParsePointError> {
+        Ok(Self::new(parse_coords(s, sep)?))
+    }
+}
+
+impl<T: fmt::Display, const N: usize> fmt::Display for Point<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, c) in self.coords.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{}", c)?;
         }
+        Ok(())
+    }
+}
+
+impl<T, const N: usize> FromStr for Point<T, N>
+where
+    T: FromStr<Err = ParseIntError>,
+{
+    type Err = ParsePointError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::new(parse_coords(s, ",")?))
+    }
+}
+
+impl<T, const N: usize> Index<usize> for Point<T, N> {
+    type Output = T;
+
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.coords[index]
+    }
+}
+
+impl<T, const N: usize> IndexMut<usize> for Point<T, N> {
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.coords[index]
+    }
+}
+
+impl<T: Copy + Add<Output = T>, const N: usize> Add for Point<T, N> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(std::array::from_fn(|i| self.coords[i] + rhs.coords[i]))
+    }
+}
+
+impl<T: Copy + Sub<Output = T>, const N: usize> Sub for Point<T, N> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(std::array::from_fn(|i| self.coords[i] - rhs.coords[i]))
+    }
+}
+
+impl<T: Copy + Neg<Output = T>, const N: usize> Neg for Point<T, N> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(self.coords.map(|c| -c))
+    }
+}
+
+impl<T: Copy + Mul<Output = T>, const N: usize> Mul<T> for Point<T, N> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::new(self.coords.map(|c| c * rhs))
+    }
+}
+
+impl<T: Copy + Div<Output = T>, const N: usize> Div<T> for Point<T, N> {
+    type Output = Self;
+
+    fn div(self, rhs: T) -> Self::Output {
+        Self::new(self.coords.map(|c| c / rhs))
+    }
+}
+
+impl<T: AddAssign, const N: usize> AddAssign for Point<T, N> {
+    fn add_assign(&mut self, rhs: Self) {
+        for (c, r) in self.coords.iter_mut().zip(rhs.coords) {
+            *c += r;
+        }
+    }
+}
+
+impl<T: SubAssign, const N: usize> SubAssign for Point<T, N> {
+    fn sub_assign(&mut self, rhs: Self) {
+        for (c, r) in self.coords.iter_mut().zip(rhs.coords) {
+            *c -= r;
+        }
+    }
+}
+
+impl<T, const N: usize> Point<T, N>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + Default,
+{
+    // Sum of the distances along each axis
+    pub fn manhattan(&self, other: &Self) -> T {
+        self.coords.iter().zip(other.coords.iter())
+            .fold(T::default(), |sum, (&a, &b)| sum + abs_diff(a, b))
+    }
+
+    // Largest distance along any axis
+    pub fn chebyshev(&self, other: &Self) -> T {
+        self.coords.iter().zip(other.coords.iter())
+            .map(|(&a, &b)| abs_diff(a, b))
+            .max()
+            .unwrap_or_default()
+    }
+}
+
+impl<T, const N: usize> Point<T, N>
+where
+    T: Copy + Add<Output = T> + From<i8>,
+{
+    // Every point differing by at most one along each axis, 3^N - 1 in all
+    pub fn neighbours(&self) -> Vec<Self> {
+        let mut neighbours = Vec::with_capacity(3usize.pow(N as u32) - 1);
+        let mut offsets = [-1i8; N];
+        loop {
+            if offsets.iter().any(|&d| d != 0) {
+                neighbours.push(Self::new(std::array::from_fn(|i| {
+                    self.coords[i] + T::from(offsets[i])
+                })));
+            }
+            // Count through the offsets like a base-3 odometer
+            let Some(i) = offsets.iter().position(|&d| d < 1) else {
+                return neighbours;
+            };
+            offsets[i] += 1;
+            offsets[..i].fill(-1);
+        }
+    }
+
+    // The 2N points one step away along a single axis
+    pub fn orthogonal_neighbours(&self) -> Vec<Self> {
+        let mut neighbours = Vec::with_capacity(2 * N);
+        for i in 0..N {
+            for d in [-1, 1] {
+                let mut p = *self;
+                p.coords[i] = p.coords[i] + T::from(d);
+                neighbours.push(p);
+            }
+        }
+        neighbours
+    }
+}
+
+impl<T> From<Point2D<T>> for Point<T, 2> {
+    fn from(p: Point2D<T>) -> Self {
+        Self::new([p.x, p.y])
+    }
+}
+
+impl<T> From<Point<T, 2>> for Point2D<T> {
+    fn from(p: Point<T, 2>) -> Self {
+        let [x, y] = p.coords;
+        Self::new(x, y)
+    }
+}
+
+impl<T> From<Point3D<T>> for Point<T, 3> {
+    fn from(p: Point3D<T>) -> Self {
+        Self::new([p.x, p.y, p.z])
+    }
+}
+
+impl<T> From<Point<T, 3>> for Point3D<T> {
+    fn from(p: Point<T, 3>) -> Self {
+        let [x, y, z] = p.coords;
+        Self::new(x, y, z)
+    }
+}
+
+// |a - b| without requiring a signed type
+fn abs_diff<T: Copy + Ord + Sub<Output = T>>(a: T, b: T) -> T {
+    if a > b { a - b } else { b - a }
+}
+
+// Componentwise arithmetic and metrics shared by every point type
+macro_rules! point_ops {
+    ($point:ident, $($c:ident),+) => {
+        impl<T: Add<Output = T>> Add for $point<T> {
+            type Output = Self;
+
+            fn add(self, rhs: Self) -> Self::Output {
+                $point { $($c: self.$c + rhs.$c),+ }
+            }
+        }
+
+        impl<T: Sub<Output = T>> Sub for $point<T> {
+            type Output = Self;
+
+            fn sub(self, rhs: Self) -> Self::Output {
+                $point { $($c: self.$c - rhs.$c),+ }
+            }
+        }
+
+        impl<T: Neg<Output = T>> Neg for $point<T> {
+            type Output = Self;
+
+            fn neg(self) -> Self::Output {
+                $point { $($c: -self.$c),+ }
+            }
+        }
+
+        impl<T: Copy + Mul<Output = T>> Mul<T> for $point<T> {
+            type Output = Self;
+
+            fn mul(self, rhs: T) -> Self::Output {
+                $point { $($c: self.$c * rhs),+ }
+            }
+        }
+
+        impl<T: Copy + Div<Output = T>> Div<T> for $point<T> {
+            type Output = Self;
+
+            fn div(self, rhs: T) -> Self::Output {
+                $point { $($c: self.$c / rhs),+ }
+            }
+        }
+
+        impl<T: AddAssign> AddAssign for $point<T> {
+            fn add_assign(&mut self, rhs: Self) {
+                $(self.$c += rhs.$c;)+
+            }
+        }
+
+        impl<T: SubAssign> SubAssign for $point<T> {
+            fn sub_assign(&mut self, rhs: Self) {
+                $(self.$c -= rhs.$c;)+
+            }
+        }
+
+        impl<T: Copy + MulAssign> MulAssign<T> for $point<T> {
+            fn mul_assign(&mut self, rhs: T) {
+                $(self.$c *= rhs;)+
+            }
+        }
+
+        impl<T: Copy + DivAssign> DivAssign<T> for $point<T> {
+            fn div_assign(&mut self, rhs: T) {
+                $(self.$c /= rhs;)+
+            }
+        }
+
+        impl<T> $point<T>
+        where
+            T: Copy + Ord + Add<Output = T> + Sub<Output = T>,
+        {
+            // Sum of the distances along each axis
+            pub fn manhattan(&self, other: &Self) -> T {
+                [$(abs_diff(self.$c, other.$c)),+].into_iter()
+                    .reduce(|a, b| a + b)
+                    .unwrap()
+            }
+
+            // Largest distance along any axis
+            pub fn chebyshev(&self, other: &Self) -> T {
+                [$(abs_diff(self.$c, other.$c)),+].into_iter()
+                    .max()
+                    .unwrap()
+            }
+
+            pub fn min(&self, other: &Self) -> Self {
+                $point { $($c: self.$c.min(other.$c)),+ }
+            }
+
+            pub fn max(&self, other: &Self) -> Self {
+                $point { $($c: self.$c.max(other.$c)),+ }
+            }
+        }
+
+        impl<T> $point<T>
+        where
+            T: Copy + Ord + Add<Output = T> + Sub<Output = T> + Mul<Output = T>,
+        {
+            // Square of the straight-line distance, which stays exact for integers
+            pub fn distance_squared(&self, other: &Self) -> T {
+                [$(abs_diff(self.$c, other.$c)),+].into_iter()
+                    .map(|d| d * d)
+                    .reduce(|a, b| a + b)
+                    .unwrap()
+            }
+        }
+
+        impl<T> $point<T>
+        where
+            T: Copy + Ord + Neg<Output = T> + From<i8>,
+        {
+            pub fn abs(&self) -> Self {
+                let zero = T::from(0);
+                $point { $($c: if self.$c < zero { -self.$c } else { self.$c }),+ }
+            }
+
+            // Each coordinate replaced by -1, 0 or 1, giving a unit step towards it
+            pub fn signum(&self) -> Self {
+                let zero = T::from(0);
+                let signum = |n: T| if n < zero {
+                    T::from(-1)
+                } else if n > zero {
+                    T::from(1)
+                } else {
+                    zero
+                };
+                $point { $($c: signum(self.$c)),+ }
+            }
+        }
+    };
+}
+
+point_ops!(Point2D, x, y);
+point_ops!(Point3D, x, y, z);
+
+impl<T> From<(T, T)> for Point2D<T> {
+    fn from((x, y): (T, T)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl<T> From<Point2D<T>> for (T, T) {
+    fn from(p: Point2D<T>) -> Self {
+        (p.x, p.y)
+    }
+}
+
+impl<T> From<(T, T, T)> for Point3D<T> {
+    fn from((x, y, z): (T, T, T)) -> Self {
+        Self::new(x, y, z)
+    }
+}
+
+impl<T> From<Point3D<T>> for (T, T, T) {
+    fn from(p: Point3D<T>) -> Self {
+        (p.x, p.y, p.z)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ParsePointError, Point, Point2D, Point3D};
+
+    #[test]
+    pub fn test_arithmetic() {
+        let mut p = Point2D::new(3, -4);
+        p += Point2D::new(1, 1);
+        assert_eq!(Point2D::new(4, -3), p);
+        assert_eq!(Point2D::new(-8, 6), -p * 2);
+        assert_eq!(Point2D::new(1, -1), p.signum());
+        assert_eq!(Point2D::new(4, 3), p.abs());
+        assert_eq!((4, -3), p.into());
+    }
+
+    #[test]
+    pub fn test_metrics() {
+        let a = Point3D::new(1, 2, 3);
+        let b = Point3D::from((4, 0, 3));
+        assert_eq!(5, a.manhattan(&b));
+        assert_eq!(3, a.chebyshev(&b));
+        assert_eq!(13, a.distance_squared(&b));
+        assert_eq!(Point3D::new(1, 0, 3), a.min(&b));
+        let (u, v) = (Point2D::new(2usize, 7), Point2D::new(5usize, 1));
+        assert_eq!(9, u.manhattan(&v));
+    }
+
+    #[test]
+    pub fn test_vectors() {
+        let a = Point3D::new(1, 2, 3);
+        let b = Point3D::new(4, -5, 6);
+        assert_eq!(12, a.dot(&b));
+        assert_eq!(Point3D::new(27, 6, -13), a.cross(&b));
+        assert_eq!(0, a.cross(&b).dot(&a));
+        assert_eq!(14, a.length_squared());
+        assert_eq!(Point3D::new(0, 0, 1), Point3D::new(1, 0, 0).cross(&Point3D::new(0, 1, 0)));
+    }
+
+    #[test]
+    pub fn test_n_dimensions() {
+        let p: Point<i32, 4> = "1,2,3,4".parse().unwrap();
+        assert_eq!(80, p.neighbours().len());
+        assert_eq!(8, p.orthogonal_neighbours().len());
+        assert!(p.neighbours().iter().all(|n| n.chebyshev(&p) == 1));
+        assert_eq!(Err(ParsePointError::WrongDimensions(3)), "1,2,3".parse::<Point<i32, 4>>());
+        assert_eq!(Err(ParsePointError::WrongDimensions(5)), "1,2,3,4,5".parse::<Point<i32, 4>>());
+        let q: Point<i32, 3> = Point3D::new(1, 2, 3).into();
+        assert_eq!(Point3D::new(2, 4, 6), (q + q).into());
+        assert_eq!(26, q.neighbours().len());
+    }
+
+    #[test]
+    pub fn test_flexible_parsing() {
+        let p: Point3D<i64> = " 19, 13, 30 ".parse().unwrap();
+        assert_eq!(Point3D::new(19, 13, 30), p);
+        assert_eq!(Ok(p), "<19,13,30>".parse());
+        assert_eq!(Ok(Point2D::new(3, -4)), "x=3, y=-4".parse());
+        assert_eq!(Ok(Point2D::new(3, -4)), Point2D::parse_with("(3 -4)", " "));
+        assert_eq!(Ok(Point::new([1, 2, 3, 4])), Point::parse_with("1;2; 3;4", ";"));
+        assert!(matches!("1,a".parse::<Point2D<i32>>(), Err(ParsePointError::BadCoord(_))));
+    }
+
+    #[test]
+    pub fn test_display_round_trip() {
+        let p = Point3D::new(-1, 0, 7);
+        assert_eq!("-1,0,7", p.to_string());
+        assert_eq!(Ok(p), p.to_string().parse());
+        assert_eq!("5,-6", Point2D::new(5, -6).to_string());
+        assert_eq!("1,2,3,4", Point::new([1, 2, 3, 4]).to_string());
+    }
+
+    #[test]
+    pub fn test_row_col() {
+        let p = Point2D::from_row_col((2, 5)).unwrap();
+        assert_eq!(Point2D::new(5, 2), p);
+        assert_eq!(None, Point2D::from_row_col((usize::MAX, 0)));
+        assert_eq!(Some((2, 5)), p.to_row_col());
+        assert_eq!(None, Point2D::new(-1, 3).to_row_col());
     }
 }
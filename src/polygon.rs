@@ -0,0 +1,146 @@
+use gcd::Gcd;
+
+use super::direction::Direction;
+use super::point::Point2D;
+
+type Vertex = Point2D<i64>;
+
+/*
+ * A simple polygon given by its vertices in order, either clockwise or
+ * anticlockwise. The last vertex joins back to the first, so it should not
+ * repeat it. Only integer arithmetic is used, so polygons far too large to
+ * rasterise are still exact.
+ */
+pub struct Polygon {
+    vertices: Vec<Vertex>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Vertex>) -> Self {
+        Self { vertices }
+    }
+
+    // Traces a path of (direction, distance) moves, such as a dig plan
+    pub fn from_moves(start: Vertex, moves: &[(Direction, i64)]) -> Self {
+        let mut vertices = Vec::with_capacity(moves.len());
+        let mut p = start;
+        for &(dir, len) in moves {
+            vertices.push(p);
+            let delta = dir.delta();
+            p += Vertex::new(delta.x as i64, delta.y as i64) * len;
+        }
+        Self { vertices }
+    }
+
+    pub fn vertices(&self) -> &[Vertex] {
+        &self.vertices
+    }
+
+    fn edges(&self) -> impl Iterator<Item = (&Vertex, &Vertex)> {
+        self.vertices.iter().zip(self.vertices.iter().cycle().skip(1))
+    }
+
+    // Twice the shoelace area, which is always an integer. Positive when the
+    // vertices run anticlockwise with y pointing up.
+    pub fn twice_signed_area(&self) -> i64 {
+        self.edges().map(|(a, b)| a.x * b.y - b.x * a.y).sum()
+    }
+
+    pub fn area(&self) -> f64 {
+        self.twice_signed_area().abs() as f64 / 2.0
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        self.edges()
+            .map(|(a, b)| (a.distance_squared(b) as f64).sqrt())
+            .sum()
+    }
+
+    // Lattice points lying on the edges
+    pub fn boundary_points(&self) -> u64 {
+        self.edges()
+            .map(|(a, b)| a.x.abs_diff(b.x).gcd(a.y.abs_diff(b.y)))
+            .sum()
+    }
+
+    // Lattice points strictly inside, by Pick's theorem: A = I + B/2 - 1.
+    // None when the vertices enclose no area, such as with fewer than three
+    // or all on one line, or cross over themselves too much for the theorem.
+    pub fn interior_points(&self) -> Option<u64> {
+        let twice_area = self.twice_signed_area().unsigned_abs();
+        if twice_area == 0 {
+            return None;
+        }
+        (twice_area + 2).checked_sub(self.boundary_points()).map(|n| n / 2)
+    }
+
+    pub fn on_boundary(&self, p: &Vertex) -> bool {
+        self.edges().any(|(a, b)| {
+            let cross = (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+            cross == 0
+                && p.x >= a.x.min(b.x) && p.x <= a.x.max(b.x)
+                && p.y >= a.y.min(b.y) && p.y <= a.y.max(b.y)
+        })
+    }
+
+    // Whether p is strictly inside, by counting edge crossings of a ray
+    // heading towards positive x
+    pub fn contains(&self, p: &Vertex) -> bool {
+        if self.on_boundary(p) {
+            return false;
+        }
+        let mut inside = false;
+        for (a, b) in self.edges() {
+            if (a.y > p.y) != (b.y > p.y) {
+                // Compare p.x with the edge's x at p.y without dividing
+                let lhs = (p.x - a.x) * (b.y - a.y);
+                let rhs = (b.x - a.x) * (p.y - a.y);
+                if (b.y > a.y && lhs < rhs) || (b.y < a.y && lhs > rhs) {
+                    inside = !inside;
+                }
+            }
+        }
+        inside
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Polygon;
+    use crate::direction::Direction;
+    use crate::point::Point2D;
+
+    #[test]
+    pub fn test_square() {
+        let square = Polygon::new(vec![
+            Point2D::new(0, 0), Point2D::new(4, 0), Point2D::new(4, 4), Point2D::new(0, 4),
+        ]);
+        assert_eq!(32, square.twice_signed_area());
+        assert_eq!(16.0, square.perimeter());
+        assert_eq!(16, square.boundary_points());
+        assert_eq!(Some(9), square.interior_points());
+        assert_eq!(None, Polygon::new(vec![Point2D::new(0, 0), Point2D::new(4, 0)]).interior_points());
+        assert_eq!(None, Polygon::new(vec![]).interior_points());
+        let flat = Polygon::new(vec![Point2D::new(0, 0), Point2D::new(2, 0), Point2D::new(4, 0)]);
+        assert_eq!(None, flat.interior_points());
+        assert_eq!(None, Polygon::new(vec![Point2D::new(1, 1); 3]).interior_points());
+        assert!(square.contains(&Point2D::new(1, 3)));
+        assert!(!square.contains(&Point2D::new(4, 2)));
+        assert!(square.on_boundary(&Point2D::new(4, 2)));
+        assert!(!square.contains(&Point2D::new(5, 2)));
+    }
+
+    #[test]
+    pub fn test_dig_plan() {
+        use Direction::*;
+        let moves = [
+            (East, 6), (South, 5), (West, 2), (South, 2), (East, 2), (South, 2), (West, 5),
+            (North, 2), (West, 1), (North, 2), (East, 2), (North, 3), (West, 2), (North, 2),
+        ];
+        let plan = Polygon::from_moves(Point2D::new(0, 0), &moves);
+        assert_eq!(Some(62), plan.interior_points().map(|n| n + plan.boundary_points()));
+        let triangle = Polygon::new(vec![Point2D::new(0, 0), Point2D::new(3, 1), Point2D::new(1, 2)]);
+        assert!(triangle.contains(&Point2D::new(1, 1)));
+        assert_eq!(Some(2), triangle.interior_points());
+    }
+}
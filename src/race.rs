@@ -15,7 +15,7 @@ impl Race {
             count += 2;
             t -= 1;
         }
-        if self.time % 2 == 0 {
+        if self.time.is_multiple_of(2) {
             count -= 1;
         }
         count
@@ -8,9 +8,25 @@ use regex::Regex;
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum ParseError {
     InvalidRange,
+    InvalidMap(RangeMapError),
     InternalError,
 }
 
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RangeMapError {
+    ZeroLength,
+    // The end of the source or destination range is past usize::MAX
+    Overflow,
+    // The source range overlaps one already in the set
+    Overlap,
+    // The source category already maps to a different category
+    ConflictingDest,
+    // The new link would let the chain return to a category it has passed
+    Cycle,
+    UnknownCategory,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub struct RangeMap {
     source: usize,
     dest: usize,
@@ -22,6 +38,23 @@ impl RangeMap {
         Self { source, dest, len }
     }
 
+    // Like new, but rejecting ranges that are empty or run past usize::MAX
+    pub fn try_new(source: usize, dest: usize, len: usize) -> Result<Self, RangeMapError> {
+        let map = Self::new(source, dest, len);
+        map.validate()?;
+        Ok(map)
+    }
+
+    fn validate(&self) -> Result<(), RangeMapError> {
+        if self.len == 0 {
+            return Err(RangeMapError::ZeroLength);
+        }
+        if self.source.checked_add(self.len).is_none() || self.dest.checked_add(self.len).is_none() {
+            return Err(RangeMapError::Overflow);
+        }
+        Ok(())
+    }
+
     pub fn map(&self, n: usize) -> Option<usize> {
         if (self.source..self.source+self.len).contains(&n) {
             Some(self.dest + n - self.source)
@@ -42,14 +75,21 @@ impl FromStr for RangeMap {
         let dest: usize = captures[1].parse().map_err(|_| ParseError::InvalidRange)?;
         let source: usize = captures[2].parse().map_err(|_| ParseError::InvalidRange)?;
         let len: usize = captures[3].parse().map_err(|_| ParseError::InvalidRange)?;
-        Ok(RangeMap::new(source, dest, len))
+        RangeMap::try_new(source, dest, len).map_err(ParseError::InvalidMap)
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct RangeMapSet {
     maps: Vec<RangeMap>,
 }
 
+impl Default for RangeMapSet {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RangeMapSet {
     pub fn new() -> Self {
         Self { maps: Vec::new() }
@@ -60,6 +100,25 @@ impl RangeMapSet {
         self.maps.sort_unstable_by_key(|map| map.source);
     }
 
+    // Like add, but rejecting invalid maps and any overlapping an existing one
+    pub fn try_add(&mut self, map: RangeMap) -> Result<(), RangeMapError> {
+        self.check(&map)?;
+        self.add(map);
+        Ok(())
+    }
+
+    fn check(&self, map: &RangeMap) -> Result<(), RangeMapError> {
+        map.validate()?;
+        // Only the neighbours either side of where it would go can overlap
+        let i = self.maps.partition_point(|m| m.source < map.source);
+        let overlaps_prev = i > 0 && self.maps[i - 1].source + self.maps[i - 1].len > map.source;
+        let overlaps_next = self.maps.get(i).is_some_and(|m| m.source < map.source + map.len);
+        if overlaps_prev || overlaps_next {
+            return Err(RangeMapError::Overlap);
+        }
+        Ok(())
+    }
+
     pub fn map(&self, n: usize) -> usize {
         let map = self.maps.binary_search_by(|map| {
             if n < map.source {
@@ -72,6 +131,131 @@ impl RangeMapSet {
         });
         map.map_or(n, |i| self.maps[i].map(n).expect("Should be in range"))
     }
+
+    pub fn maps(&self) -> &[RangeMap] {
+        &self.maps
+    }
+
+    /*
+     * Splits a range wherever a map starts or ends, giving each piece along
+     * with where its first number maps to. The cost depends on the number of
+     * maps rather than the length of the range.
+     */
+    fn split(&self, range: &Range<usize>) -> Vec<(Range<usize>, usize)> {
+        let mut pieces = Vec::new();
+        let mut start = range.start;
+        // Skip the maps ending before this range starts
+        let first = self.maps.partition_point(|map| map.source + map.len <= start);
+        for map in &self.maps[first..] {
+            if start >= range.end || map.source >= range.end {
+                break;
+            }
+            if start < map.source {
+                // Unmapped numbers in front of this map keep their values
+                pieces.push((start..map.source, start));
+                start = map.source;
+            }
+            let end = range.end.min(map.source + map.len);
+            pieces.push((start..end, map.dest + start - map.source));
+            start = end;
+        }
+        if start < range.end {
+            pieces.push((start..range.end, start));
+        }
+        pieces
+    }
+
+    // Maps whole ranges at once. The pieces are not sorted or merged.
+    pub fn map_ranges(&self, ranges: &[Range<usize>]) -> Vec<Range<usize>> {
+        ranges.iter()
+            .flat_map(|range| self.split(range))
+            .map(|(piece, dest)| dest..dest + piece.len())
+            .collect()
+    }
+
+    // A single set equivalent to mapping with self and then with next
+    pub fn compose(&self, next: &RangeMapSet) -> RangeMapSet {
+        let mut maps = Vec::new();
+        for (piece, dest) in self.split(&(0..usize::MAX)) {
+            for (image, next_dest) in next.split(&(dest..dest + piece.len())) {
+                let source = piece.start + image.start - dest;
+                // Numbers that end up where they started need no map
+                if source != next_dest {
+                    maps.push(RangeMap::new(source, next_dest, image.len()));
+                }
+            }
+        }
+        maps.sort_unstable_by_key(|map| map.source);
+        RangeMapSet { maps }
+    }
+
+    pub fn inverse(&self) -> InverseRangeMapSet {
+        let mut maps: Vec<RangeMap> = self.maps.iter()
+            .map(|map| RangeMap::new(map.dest, map.source, map.len))
+            .collect();
+        maps.sort_unstable_by_key(|map| map.source);
+        let covered = self.maps.iter().map(|map| map.source..map.source + map.len).collect();
+        InverseRangeMapSet { maps, covered }
+    }
+}
+
+/*
+ * Maps numbers back through a RangeMapSet. Several numbers can map to the
+ * same one, such as a number left alone by every map and another mapped onto
+ * it, so each lookup gives every preimage, and possibly none.
+ */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InverseRangeMapSet {
+    // Each forward map turned around, so these may overlap
+    maps: Vec<RangeMap>,
+    // The forward source ranges, sorted, outside which numbers map to themselves
+    covered: Vec<Range<usize>>,
+}
+
+impl InverseRangeMapSet {
+    fn is_covered(&self, n: usize) -> bool {
+        let i = self.covered.partition_point(|range| range.end <= n);
+        self.covered.get(i).is_some_and(|range| range.contains(&n))
+    }
+
+    pub fn preimages(&self, n: usize) -> Vec<usize> {
+        let mut preimages: Vec<usize> = self.maps.iter().filter_map(|map| map.map(n)).collect();
+        if !self.is_covered(n) {
+            preimages.push(n);
+        }
+        preimages.sort_unstable();
+        preimages
+    }
+
+    // Every number mapping into the given ranges, as unsorted and unmerged ranges
+    pub fn preimage_ranges(&self, ranges: &[Range<usize>]) -> Vec<Range<usize>> {
+        let mut preimages = Vec::new();
+        for range in ranges {
+            for map in &self.maps {
+                let start = range.start.max(map.source);
+                let end = range.end.min(map.source + map.len);
+                if start < end {
+                    preimages.push(map.dest + start - map.source..map.dest + end - map.source);
+                }
+            }
+            // The parts of the range no forward map covers are their own preimages
+            let mut start = range.start;
+            let first = self.covered.partition_point(|c| c.end <= start);
+            for covered in &self.covered[first..] {
+                if start >= range.end || covered.start >= range.end {
+                    break;
+                }
+                if start < covered.start {
+                    preimages.push(start..covered.start);
+                }
+                start = start.max(covered.end);
+            }
+            if start < range.end {
+                preimages.push(start..range.end);
+            }
+        }
+        preimages
+    }
 }
 
 pub struct RangeMapChain {
@@ -79,6 +263,12 @@ pub struct RangeMapChain {
     chains: HashMap<String, String>,
 }
 
+impl Default for RangeMapChain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl RangeMapChain {
     pub fn new() -> Self {
         Self { sets: HashMap::new(), chains: HashMap::new() }
@@ -87,31 +277,306 @@ impl RangeMapChain {
     pub fn add(&mut self, source: &str, dest: &str, map: RangeMap) {
         self.chains.insert(source.to_owned(), dest.to_owned());
         let set = self.sets.entry(source.to_owned())
-            .or_insert(RangeMapSet::new());
+            .or_default();
         set.add(map);
     }
 
-    pub fn map(&self, source: &str, n: usize) -> usize {
-        let mut n = n;
+    /*
+     * Like add, but checking that the map is valid for its set, that source
+     * doesn't already lead somewhere else and that following the chain from
+     * dest never comes back to source. Nothing is added on error.
+     */
+    pub fn try_add(&mut self, source: &str, dest: &str, map: RangeMap) -> Result<(), RangeMapError> {
+        if self.chains.get(source).is_some_and(|d| d != dest) {
+            return Err(RangeMapError::ConflictingDest);
+        }
+        let mut next = Some(dest);
+        // Bounded in case cycles were let in through add
+        for _ in 0..=self.chains.len() {
+            match next {
+                Some(category) if category == source => return Err(RangeMapError::Cycle),
+                Some(category) => next = self.chains.get(category).map(String::as_str),
+                None => break,
+            }
+        }
+        match self.sets.get(source) {
+            Some(set) => set.check(&map)?,
+            None => map.validate()?,
+        }
+        self.add(source, dest, map);
+        Ok(())
+    }
+
+    fn contains(&self, category: &str) -> bool {
+        self.chains.contains_key(category) || self.chains.values().any(|dest| dest == category)
+    }
+
+    // The sets met following the chain from source, or an error if it
+    // takes more links than there are, going round a cycle let in by add
+    fn path(&self, source: &str) -> Result<Vec<&RangeMapSet>, RangeMapError> {
+        let mut sets = Vec::new();
         let mut source = source;
         while let Some(dest) = self.chains.get(source) {
+            if sets.len() == self.chains.len() {
+                return Err(RangeMapError::Cycle);
+            }
             let set = self.sets.get(source)
                 .expect("Unknown source");
-            n = set.map(n);
+            sets.push(set);
             source = dest;
         }
-        n
+        Ok(sets)
+    }
+
+    // Like map, but an error for a category the chain doesn't know about
+    // rather than leaving n unchanged, and for a cycle rather than panicking
+    pub fn try_map(&self, source: &str, n: usize) -> Result<usize, RangeMapError> {
+        if !self.contains(source) {
+            return Err(RangeMapError::UnknownCategory);
+        }
+        Ok(self.path(source)?.iter().fold(n, |n, set| set.map(n)))
+    }
+
+    pub fn map(&self, source: &str, n: usize) -> usize {
+        let path = self.path(source)
+            .expect("Cycle in chain");
+        path.iter().fold(n, |n, set| set.map(n))
     }
 
     pub fn map_range(&self, source: &str, ns: &Range<usize>) -> Vec<usize> {
-        let mut ns: Vec<usize> = ns.clone().collect();
+        let path = self.path(source)
+            .expect("Cycle in chain");
+        ns.clone().map(|n| path.iter().fold(n, |n, set| set.map(n))).collect()
+    }
+
+    // The chain run backwards, with every set inverted once up front
+    pub fn inverse(&self) -> InverseRangeMapChain {
+        let mut sources: HashMap<String, Vec<(String, InverseRangeMapSet)>> = HashMap::new();
+        for (source, dest) in &self.chains {
+            let set = self.sets.get(source)
+                .expect("Unknown source");
+            sources.entry(dest.clone()).or_default().push((source.clone(), set.inverse()));
+        }
+        InverseRangeMapChain { sources, links: self.chains.len() }
+    }
+
+    // A single set mapping source straight to dest, if the chain leads there
+    pub fn compose(&self, source: &str, dest: &str) -> Option<RangeMapSet> {
+        let mut composed = RangeMapSet::new();
         let mut source = source;
-        while let Some(dest) = self.chains.get(source) {
+        // Bounded in case cycles were let in through add
+        for _ in 0..=self.chains.len() {
+            if source == dest {
+                return Some(composed);
+            }
+            let next = self.chains.get(source)?;
             let set = self.sets.get(source)
                 .expect("Unknown source");
-            ns = ns.iter().map(|n| set.map(*n)).collect();
-            source = dest;
+            composed = composed.compose(set);
+            source = next;
         }
-        ns
+        None
+    }
+
+    // Like map_range, but keeping the numbers as ranges all the way through
+    pub fn map_ranges(&self, source: &str, ranges: &[Range<usize>]) -> Vec<Range<usize>> {
+        let path = self.path(source)
+            .expect("Cycle in chain");
+        path.iter().fold(ranges.to_vec(), |ranges, set| set.map_ranges(&ranges))
+    }
+}
+
+/*
+ * A RangeMapChain run backwards, from any category to the categories the
+ * chain starts from. Where several categories map into the same one, the walk
+ * follows all of them and keeps the preimages apart by starting category.
+ */
+pub struct InverseRangeMapChain {
+    // For each category, those mapping into it along with their inverted sets
+    sources: HashMap<String, Vec<(String, InverseRangeMapSet)>>,
+    links: usize,
+}
+
+impl InverseRangeMapChain {
+    fn walk<T, F>(&self, dest: &str, values: Vec<T>, step: F) -> HashMap<String, Vec<T>>
+    where
+        F: Fn(&InverseRangeMapSet, &[T]) -> Vec<T>,
+    {
+        let mut found: HashMap<String, Vec<T>> = HashMap::new();
+        let mut pending = vec![(dest, values, 0)];
+        while let Some((category, values, steps)) = pending.pop() {
+            match self.sources.get(category) {
+                // A walk longer than the number of links must be going round
+                // a cycle let in through add, so it has no starting category
+                Some(_) if steps >= self.links => {}
+                Some(sources) => {
+                    for (source, inverse) in sources {
+                        pending.push((source, step(inverse, &values), steps + 1));
+                    }
+                }
+                None => found.entry(category.to_owned()).or_default().extend(values),
+            }
+        }
+        found
+    }
+
+    // Every number that maps to n in dest, keyed by the starting category it's in
+    pub fn preimages(&self, dest: &str, n: usize) -> HashMap<String, Vec<usize>> {
+        let mut found = self.walk(dest, vec![n], |inverse, ns| {
+            ns.iter().flat_map(|n| inverse.preimages(*n)).collect()
+        });
+        for ns in found.values_mut() {
+            ns.sort_unstable();
+        }
+        found
+    }
+
+    // Like preimages, but for ranges of numbers in dest
+    pub fn preimage_ranges(&self, dest: &str, ranges: &[Range<usize>]) -> HashMap<String, Vec<Range<usize>>> {
+        self.walk(dest, ranges.to_vec(), |inverse, ranges| inverse.preimage_ranges(ranges))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashMap;
+
+    use super::{ParseError, RangeMap, RangeMapChain, RangeMapError, RangeMapSet};
+
+    fn example() -> RangeMapChain {
+        let maps = [
+            ("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]),
+            ("soil", "fertilizer", vec![(0, 15, 37), (37, 52, 2), (39, 0, 15)]),
+            ("fertilizer", "water", vec![(49, 53, 8), (0, 11, 42), (42, 0, 7), (57, 7, 4)]),
+            ("water", "light", vec![(88, 18, 7), (18, 25, 70)]),
+            ("light", "temperature", vec![(45, 77, 23), (81, 45, 19), (68, 64, 13)]),
+            ("temperature", "humidity", vec![(0, 69, 1), (1, 0, 69)]),
+            ("humidity", "location", vec![(60, 56, 37), (56, 93, 4)]),
+        ];
+        let mut chain = RangeMapChain::new();
+        for (source, dest, ranges) in maps {
+            for (d, s, len) in ranges {
+                chain.try_add(source, dest, RangeMap::new(s, d, len)).unwrap();
+            }
+        }
+        chain
+    }
+
+    #[test]
+    pub fn test_map_ranges() {
+        let mut set = RangeMapSet::new();
+        set.add(RangeMap::new(10, 100, 5));
+        set.add(RangeMap::new(20, 0, 5));
+        assert_eq!(vec![5..10, 100..105, 15..20, 0..2, 102..104], set.map_ranges(&[5..22, 12..14]));
+        assert_eq!(vec![30..40], set.map_ranges(&[3..3, 30..40]));
+
+        let chain = example();
+        let seeds = [79..93, 55..68];
+        let mut expected: Vec<usize> = seeds.iter().flat_map(|r| chain.map_range("seed", r)).collect();
+        let mut mapped: Vec<usize> = chain.map_ranges("seed", &seeds).into_iter().flatten().collect();
+        expected.sort_unstable();
+        mapped.sort_unstable();
+        assert_eq!(expected, mapped);
+        assert_eq!(Some(46), chain.map_ranges("seed", &seeds).iter().map(|r| r.start).min());
+    }
+
+    #[test]
+    pub fn test_compose() {
+        let chain = example();
+        let composed = chain.compose("seed", "location").unwrap();
+        assert!((0..150).all(|n| composed.map(n) == chain.map("seed", n)));
+        assert!(composed.maps().windows(2).all(|w| w[0].source + w[0].len <= w[1].source));
+        let soil = chain.compose("soil", "water").unwrap();
+        assert!((0..150).all(|n| soil.map(n) == chain.sets["fertilizer"].map(chain.sets["soil"].map(n))));
+        assert_eq!(Some(RangeMapSet::new()), chain.compose("water", "water"));
+        assert_eq!(None, chain.compose("location", "seed"));
+    }
+
+    #[test]
+    pub fn test_inverse() {
+        let mut set = RangeMapSet::new();
+        set.add(RangeMap::new(10, 20, 10));
+        set.add(RangeMap::new(20, 5, 5));
+        let inverse = set.inverse();
+        for n in 0..40 {
+            let expected: Vec<usize> = (0..40).filter(|&x| set.map(x) == n).collect();
+            assert_eq!(expected, inverse.preimages(n));
+        }
+        assert_eq!(vec![20..23, 0..8, 15..20, 25..30], inverse.preimage_ranges(&[0..8, 25..30]));
+
+        let chain = example();
+        let inverse = chain.inverse();
+        for n in 0..100 {
+            let expected: Vec<usize> = (0..150).filter(|&x| chain.map("seed", x) == n).collect();
+            assert_eq!(expected, inverse.preimages("location", n)["seed"]);
+            let ranges = [n..n + 1, 200..200];
+            let mut seeds: Vec<usize> = inverse.preimage_ranges("location", &ranges)["seed"].iter().cloned().flatten().collect();
+            seeds.sort_unstable();
+            assert_eq!(expected, seeds);
+        }
+        assert_eq!(vec![79], inverse.preimages("soil", 81)["seed"]);
+        assert_eq!(vec![98], inverse.preimages("soil", 50)["seed"]);
+        assert_eq!(1, inverse.preimages("soil", 50).len());
+
+        // Two categories leading into the same one are both followed, and
+        // each keeps its own preimages, like 12 where neither set moves it
+        let mut merge = RangeMapChain::new();
+        merge.try_add("a", "c", RangeMap::new(0, 10, 5)).unwrap();
+        merge.try_add("b", "c", RangeMap::new(20, 12, 5)).unwrap();
+        merge.try_add("c", "d", RangeMap::new(12, 100, 1)).unwrap();
+        let keyed = |a: Vec<usize>, b: Vec<usize>| HashMap::from([("a".to_owned(), a), ("b".to_owned(), b)]);
+        assert_eq!(keyed(vec![2, 12, 100], vec![12, 20, 100]), merge.inverse().preimages("d", 100));
+        assert_eq!(keyed(vec![2, 12], vec![12, 20]), merge.inverse().preimages("c", 12));
+        let ranges = merge.inverse().preimage_ranges("c", &[12..13, 30..31]);
+        assert_eq!(vec![2..3, 12..13, 30..31], ranges["a"]);
+        assert_eq!(vec![20..21, 12..13, 30..31], ranges["b"]);
+    }
+
+    #[test]
+    pub fn test_validation() {
+        assert_eq!(Err(RangeMapError::ZeroLength), RangeMap::try_new(1, 2, 0));
+        assert_eq!(Err(RangeMapError::Overflow), RangeMap::try_new(usize::MAX - 1, 0, 3));
+        assert_eq!(Err(RangeMapError::Overflow), RangeMap::try_new(0, usize::MAX, 1));
+        assert_eq!(Err(ParseError::InvalidMap(RangeMapError::ZeroLength)), "50 98 0".parse::<RangeMap>());
+
+        let mut set = RangeMapSet::new();
+        set.try_add(RangeMap::new(10, 0, 5)).unwrap();
+        set.try_add(RangeMap::new(20, 0, 5)).unwrap();
+        set.try_add(RangeMap::new(15, 0, 5)).unwrap();
+        assert_eq!(Err(RangeMapError::Overlap), set.try_add(RangeMap::new(24, 0, 3)));
+        assert_eq!(Err(RangeMapError::Overlap), set.try_add(RangeMap::new(5, 0, 6)));
+        assert_eq!(Err(RangeMapError::Overlap), set.try_add(RangeMap::new(0, 0, 50)));
+        assert_eq!(3, set.maps().len());
+
+        let mut chain = example();
+        let map = || RangeMap::new(200, 0, 1);
+        assert_eq!(Err(RangeMapError::ConflictingDest), chain.try_add("seed", "water", map()));
+        assert_eq!(Err(RangeMapError::Cycle), chain.try_add("location", "seed", map()));
+        assert_eq!(Err(RangeMapError::Cycle), chain.try_add("gps", "gps", map()));
+        assert_eq!(Err(RangeMapError::Overlap), chain.try_add("seed", "soil", RangeMap::new(99, 0, 5)));
+        assert_eq!(Err(RangeMapError::UnknownCategory), chain.try_map("location", 0).and(chain.try_map("sed", 0)));
+        chain.try_add("location", "gps", map()).unwrap();
+        assert_eq!(Ok(0), chain.try_map("humidity", 200));
+
+        // Walks over a cycle let in through add give up instead of looping
+        chain.add("gps", "seed", map());
+        assert_eq!(Err(RangeMapError::Cycle), chain.try_map("seed", 79));
+        assert_eq!(None, chain.compose("seed", "nowhere"));
+        assert!(chain.compose("seed", "gps").is_some());
+        assert!(chain.inverse().preimages("location", 0).is_empty());
+
+        let mut cycle = RangeMapChain::new();
+        cycle.try_add("a", "b", RangeMap::new(0, 10, 5)).unwrap();
+        cycle.add("b", "a", RangeMap::new(10, 0, 5));
+        assert_eq!(Err(RangeMapError::Cycle), cycle.try_map("a", 5));
+    }
+
+    #[test]
+    #[should_panic(expected = "Cycle in chain")]
+    pub fn test_map_cycle() {
+        let mut cycle = RangeMapChain::new();
+        cycle.add("a", "b", RangeMap::new(0, 10, 5));
+        cycle.add("b", "a", RangeMap::new(10, 0, 5));
+        cycle.map_ranges("a", &[0..5, 10..15]);
     }
 }
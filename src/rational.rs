@@ -0,0 +1,274 @@
+use std::cmp::Ordering;
+use std::fmt;
+use std::num::ParseIntError;
+use std::ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use std::str::FromStr;
+
+use gcd::Gcd;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseRationalError {
+    BadNumber(ParseIntError),
+    ZeroDenominator,
+}
+
+/*
+ * An exact fraction, always kept in lowest terms with a positive denominator
+ * so that equal values compare and hash equal. Arithmetic panics on overflow
+ * rather than silently losing precision.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+fn gcd(a: i128, b: i128) -> i128 {
+    a.unsigned_abs().gcd(b.unsigned_abs()) as i128
+}
+
+fn checked(n: Option<i128>) -> i128 {
+    n.expect("Rational overflow")
+}
+
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+    pub const ONE: Rational = Rational { num: 1, den: 1 };
+
+    pub fn new(num: i128, den: i128) -> Self {
+        assert!(den != 0, "zero denominator");
+        // gcd(0, i128::MIN) is 2^127, which doesn't fit back in an i128
+        if num == 0 {
+            return Self::ZERO;
+        }
+        let g = gcd(num, den).max(1);
+        let (num, den) = (num / g, den / g);
+        if den < 0 {
+            Self { num: checked(num.checked_neg()), den: checked(den.checked_neg()) }
+        } else {
+            Self { num, den }
+        }
+    }
+
+    pub fn numer(&self) -> i128 {
+        self.num
+    }
+
+    pub fn denom(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn to_integer(&self) -> Option<i128> {
+        if self.is_integer() { Some(self.num) } else { None }
+    }
+
+    pub fn floor(&self) -> i128 {
+        self.num.div_euclid(self.den)
+    }
+
+    pub fn ceil(&self) -> i128 {
+        -checked(self.num.checked_neg()).div_euclid(self.den)
+    }
+
+    pub fn abs(&self) -> Self {
+        Self { num: checked(self.num.checked_abs()), den: self.den }
+    }
+
+    pub fn signum(&self) -> i128 {
+        self.num.signum()
+    }
+
+    pub fn recip(&self) -> Self {
+        Self::new(self.den, self.num)
+    }
+
+    pub fn to_f64(&self) -> f64 {
+        self.num as f64 / self.den as f64
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(n: i128) -> Self {
+        Self { num: n, den: 1 }
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(n: i64) -> Self {
+        Self::from(n as i128)
+    }
+}
+
+impl From<isize> for Rational {
+    fn from(n: isize) -> Self {
+        Self::from(n as i128)
+    }
+}
+
+// Lets generic code such as Point2D::signum and Rect build small constants
+impl From<i8> for Rational {
+    fn from(n: i8) -> Self {
+        Self::from(n as i128)
+    }
+}
+
+impl From<u8> for Rational {
+    fn from(n: u8) -> Self {
+        Self::from(n as i128)
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        // Denominators are positive, so cross-multiplying keeps the order
+        let lhs = checked(self.num.checked_mul(other.den));
+        let rhs = checked(other.num.checked_mul(self.den));
+        lhs.cmp(&rhs)
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, rhs: Rational) -> Self::Output {
+        let g = gcd(self.den, rhs.den);
+        let lhs = checked(self.num.checked_mul(rhs.den / g));
+        let rhs_num = checked(rhs.num.checked_mul(self.den / g));
+        let den = checked((self.den / g).checked_mul(rhs.den));
+        Rational::new(checked(lhs.checked_add(rhs_num)), den)
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, rhs: Rational) -> Self::Output {
+        self + -rhs
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, rhs: Rational) -> Self::Output {
+        // Cancel before multiplying to keep intermediates small
+        let g1 = gcd(self.num, rhs.den).max(1);
+        let g2 = gcd(rhs.num, self.den).max(1);
+        let num = checked((self.num / g1).checked_mul(rhs.num / g2));
+        let den = checked((self.den / g2).checked_mul(rhs.den / g1));
+        Rational::new(num, den)
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    fn div(self, rhs: Rational) -> Self::Output {
+        Mul::mul(self, rhs.recip())
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Self::Output {
+        Rational { num: checked(self.num.checked_neg()), den: self.den }
+    }
+}
+
+impl AddAssign for Rational {
+    fn add_assign(&mut self, rhs: Rational) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Rational {
+    fn sub_assign(&mut self, rhs: Rational) {
+        *self = *self - rhs;
+    }
+}
+
+impl MulAssign for Rational {
+    fn mul_assign(&mut self, rhs: Rational) {
+        *self = *self * rhs;
+    }
+}
+
+impl DivAssign for Rational {
+    fn div_assign(&mut self, rhs: Rational) {
+        *self = *self / rhs;
+    }
+}
+
+impl fmt::Display for Rational {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl FromStr for Rational {
+    type Err = ParseRationalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (num, den) = s.split_once('/').unwrap_or((s, "1"));
+        let num = num.trim().parse::<i128>().map_err(Self::Err::BadNumber)?;
+        let den = den.trim().parse::<i128>().map_err(Self::Err::BadNumber)?;
+        if den == 0 {
+            return Err(Self::Err::ZeroDenominator);
+        }
+        Ok(Rational::new(num, den))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Rational;
+
+    #[test]
+    pub fn test_arithmetic() {
+        let a = Rational::new(6, -8);
+        let b: Rational = "5/6".parse().unwrap();
+        assert_eq!((-3, 4), (a.numer(), a.denom()));
+        assert_eq!(Rational::new(1, 12), a + b);
+        assert_eq!(Rational::new(-19, 12), a - b);
+        assert_eq!(Rational::new(-5, 8), a * b);
+        assert_eq!(Rational::new(-9, 10), a / b);
+        assert!(a < b);
+        assert_eq!((-1, 0), (a.floor(), a.ceil()));
+        assert_eq!(Rational::ZERO, Rational::new(0, i128::MIN));
+        assert_eq!("-3/4", a.to_string());
+        assert_eq!(Some(2), (b * Rational::from(12i64) / Rational::from(5i64)).to_integer());
+    }
+
+    #[test]
+    #[should_panic(expected = "Rational overflow")]
+    pub fn test_negation_overflow() {
+        let _ = -Rational::from(i128::MIN);
+    }
+
+    #[test]
+    #[should_panic(expected = "Rational overflow")]
+    pub fn test_new_overflow() {
+        let _ = Rational::new(i128::MIN, -1);
+    }
+}
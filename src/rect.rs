@@ -0,0 +1,216 @@
+use std::ops::{Add, Mul, Sub};
+
+use super::point::{Point2D, Point3D};
+
+/*
+ * Rectangles and cuboids span from `min` to `max` inclusive, so a single
+ * point is a valid 1x1 rectangle. Integer coordinates are assumed wherever a
+ * size or step is involved.
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rect<T> {
+    pub min: Point2D<T>,
+    pub max: Point2D<T>,
+}
+
+impl<T> Rect<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + From<u8>,
+{
+    // The rectangle with opposite corners at a and b, in any order
+    pub fn new(a: Point2D<T>, b: Point2D<T>) -> Self {
+        Self { min: a.min(&b), max: a.max(&b) }
+    }
+
+    // The smallest rectangle containing every point
+    pub fn bounding(points: &[Point2D<T>]) -> Option<Self> {
+        let first = *points.first()?;
+        Some(points.iter().fold(Self::new(first, first), |rect, p| {
+            Self { min: rect.min.min(p), max: rect.max.max(p) }
+        }))
+    }
+
+    pub fn width(&self) -> T {
+        self.max.x - self.min.x + T::from(1)
+    }
+
+    pub fn height(&self) -> T {
+        self.max.y - self.min.y + T::from(1)
+    }
+
+    pub fn area(&self) -> T {
+        self.width() * self.height()
+    }
+
+    pub fn contains(&self, p: &Point2D<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x) && (self.min.y..=self.max.y).contains(&p.y)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min = self.min.max(&other.min);
+        let max = self.max.min(&other.max);
+        if min.x <= max.x && min.y <= max.y {
+            Some(Self { min, max })
+        } else {
+            None
+        }
+    }
+
+    // The smallest rectangle containing both
+    pub fn union(&self, other: &Self) -> Self {
+        Self { min: self.min.min(&other.min), max: self.max.max(&other.max) }
+    }
+
+    // Every contained point, row by row
+    pub fn points(&self) -> impl Iterator<Item = Point2D<T>> {
+        let (min, max) = (self.min, self.max);
+        steps(min.y, max.y).flat_map(move |y| steps(min.x, max.x).map(move |x| Point2D::new(x, y)))
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Cuboid<T> {
+    pub min: Point3D<T>,
+    pub max: Point3D<T>,
+}
+
+impl<T> Cuboid<T>
+where
+    T: Copy + Ord + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + From<u8>,
+{
+    // The cuboid with opposite corners at a and b, in any order
+    pub fn new(a: Point3D<T>, b: Point3D<T>) -> Self {
+        Self { min: a.min(&b), max: a.max(&b) }
+    }
+
+    // The smallest cuboid containing every point
+    pub fn bounding(points: &[Point3D<T>]) -> Option<Self> {
+        let first = *points.first()?;
+        Some(points.iter().fold(Self::new(first, first), |cuboid, p| {
+            Self { min: cuboid.min.min(p), max: cuboid.max.max(p) }
+        }))
+    }
+
+    // (x, y, z) extents
+    pub fn size(&self) -> Point3D<T> {
+        let one = T::from(1);
+        Point3D::new(
+            self.max.x - self.min.x + one,
+            self.max.y - self.min.y + one,
+            self.max.z - self.min.z + one,
+        )
+    }
+
+    pub fn volume(&self) -> T {
+        let size = self.size();
+        size.x * size.y * size.z
+    }
+
+    pub fn contains(&self, p: &Point3D<T>) -> bool {
+        (self.min.x..=self.max.x).contains(&p.x)
+            && (self.min.y..=self.max.y).contains(&p.y)
+            && (self.min.z..=self.max.z).contains(&p.z)
+    }
+
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let min = self.min.max(&other.min);
+        let max = self.max.min(&other.max);
+        if min.x <= max.x && min.y <= max.y && min.z <= max.z {
+            Some(Self { min, max })
+        } else {
+            None
+        }
+    }
+
+    // The smallest cuboid containing both
+    pub fn union(&self, other: &Self) -> Self {
+        Self { min: self.min.min(&other.min), max: self.max.max(&other.max) }
+    }
+
+    /*
+     * Splits off the parts of self outside other, as at most six disjoint
+     * cuboids. Slabs are peeled off along x, then y, then z, each time
+     * narrowing the remainder to the overlap on that axis.
+     */
+    pub fn subtract(&self, other: &Self) -> Vec<Self> {
+        let Some(overlap) = self.intersection(other) else {
+            return vec![*self];
+        };
+        let one = T::from(1);
+        let mut pieces = Vec::new();
+        let mut rest = *self;
+        if rest.min.x < overlap.min.x {
+            pieces.push(Self { max: Point3D::new(overlap.min.x - one, rest.max.y, rest.max.z), ..rest });
+        }
+        if overlap.max.x < rest.max.x {
+            pieces.push(Self { min: Point3D::new(overlap.max.x + one, rest.min.y, rest.min.z), ..rest });
+        }
+        rest.min.x = overlap.min.x;
+        rest.max.x = overlap.max.x;
+        if rest.min.y < overlap.min.y {
+            pieces.push(Self { max: Point3D::new(rest.max.x, overlap.min.y - one, rest.max.z), ..rest });
+        }
+        if overlap.max.y < rest.max.y {
+            pieces.push(Self { min: Point3D::new(rest.min.x, overlap.max.y + one, rest.min.z), ..rest });
+        }
+        rest.min.y = overlap.min.y;
+        rest.max.y = overlap.max.y;
+        if rest.min.z < overlap.min.z {
+            pieces.push(Self { max: Point3D::new(rest.max.x, rest.max.y, overlap.min.z - one), ..rest });
+        }
+        if overlap.max.z < rest.max.z {
+            pieces.push(Self { min: Point3D::new(rest.min.x, rest.min.y, overlap.max.z + one), ..rest });
+        }
+        pieces
+    }
+
+    // Every contained point, with x varying fastest
+    pub fn points(&self) -> impl Iterator<Item = Point3D<T>> {
+        let (min, max) = (self.min, self.max);
+        steps(min.z, max.z).flat_map(move |z| {
+            steps(min.y, max.y).flat_map(move |y| {
+                steps(min.x, max.x).map(move |x| Point3D::new(x, y, z))
+            })
+        })
+    }
+}
+
+// from..=to for any integer type, without overflowing past `to`
+fn steps<T>(from: T, to: T) -> impl Iterator<Item = T>
+where
+    T: Copy + Ord + Add<Output = T> + From<u8>,
+{
+    let first = if from <= to { Some(from) } else { None };
+    std::iter::successors(first, move |&n| if n < to { Some(n + T::from(1)) } else { None })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Cuboid, Rect};
+    use crate::point::{Point2D, Point3D};
+
+    #[test]
+    pub fn test_rect() {
+        let a = Rect::new(Point2D::new(3, 4), Point2D::new(0, 0));
+        let b = Rect::bounding(&[Point2D::new(2, 3), Point2D::new(5, 6), Point2D::new(4, 2)]).unwrap();
+        assert_eq!(20, a.area());
+        assert_eq!(Some(Rect::new(Point2D::new(2, 2), Point2D::new(3, 4))), a.intersection(&b));
+        assert_eq!(42, a.union(&b).area());
+        assert_eq!(a.area() as usize, a.points().filter(|p| a.contains(p)).count());
+        assert_eq!(None, a.intersection(&Rect::new(Point2D::new(4, 0), Point2D::new(5, 0))));
+    }
+
+    #[test]
+    pub fn test_cuboid_subtract() {
+        let big = Cuboid::new(Point3D::new(0i64, 0, 0), Point3D::new(9, 9, 9));
+        let hole = Cuboid::new(Point3D::new(2, 3, 4), Point3D::new(5, 12, 6));
+        let pieces = big.subtract(&hole);
+        let overlap = big.intersection(&hole).unwrap();
+        assert_eq!(big.volume() - overlap.volume(), pieces.iter().map(Cuboid::volume).sum());
+        for (i, a) in pieces.iter().enumerate() {
+            assert_eq!(None, a.intersection(&hole));
+            assert!(pieces[i + 1..].iter().all(|b| a.intersection(b).is_none()));
+        }
+        assert_eq!(vec![big], big.subtract(&Cuboid::new(Point3D::new(10, 0, 0), Point3D::new(11, 1, 1))));
+    }
+}
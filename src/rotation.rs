@@ -0,0 +1,134 @@
+use std::ops::Neg;
+
+use super::point::Point3D;
+
+/*
+ * One of the 24 ways to turn a cube so that its faces stay aligned with the
+ * axes, as a 3x3 matrix with a single 1 or -1 in each row and column and a
+ * determinant of 1 (so never a reflection). Applying it to a column vector
+ * maps (x, y, z) to matrix * (x, y, z).
+ */
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct Rotation {
+    matrix: [[i8; 3]; 3],
+}
+
+impl Rotation {
+    pub const IDENTITY: Rotation = Rotation { matrix: [[1, 0, 0], [0, 1, 0], [0, 0, 1]] };
+
+    // Every rotation, starting with the identity
+    pub fn all() -> Vec<Rotation> {
+        let perms = [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+        let mut rotations = Vec::with_capacity(24);
+        for perm in perms {
+            for signs in 0..8 {
+                let mut matrix = [[0; 3]; 3];
+                for (row, &col) in perm.iter().enumerate() {
+                    matrix[row][col] = if signs & (1 << row) == 0 { 1 } else { -1 };
+                }
+                let rotation = Rotation { matrix };
+                if rotation.determinant() == 1 {
+                    rotations.push(rotation);
+                }
+            }
+        }
+        rotations
+    }
+
+    // Quarter turns anticlockwise when looking down each axis towards the origin
+    pub fn about_x() -> Rotation {
+        Rotation { matrix: [[1, 0, 0], [0, 0, -1], [0, 1, 0]] }
+    }
+
+    pub fn about_y() -> Rotation {
+        Rotation { matrix: [[0, 0, 1], [0, 1, 0], [-1, 0, 0]] }
+    }
+
+    pub fn about_z() -> Rotation {
+        Rotation { matrix: [[0, -1, 0], [1, 0, 0], [0, 0, 1]] }
+    }
+
+    pub fn matrix(&self) -> [[i8; 3]; 3] {
+        self.matrix
+    }
+
+    fn determinant(&self) -> i8 {
+        let m = &self.matrix;
+        m[0][0] * (m[1][1] * m[2][2] - m[1][2] * m[2][1])
+            - m[0][1] * (m[1][0] * m[2][2] - m[1][2] * m[2][0])
+            + m[0][2] * (m[1][0] * m[2][1] - m[1][1] * m[2][0])
+    }
+
+    // Every entry is 0 or ±1, so no multiplication is needed
+    pub fn apply<T>(&self, p: Point3D<T>) -> Point3D<T>
+    where
+        T: Copy + Neg<Output = T>,
+    {
+        let coords = [p.x, p.y, p.z];
+        let row = |r: [i8; 3]| {
+            let col = r.iter().position(|&n| n != 0).unwrap();
+            if r[col] > 0 { coords[col] } else { -coords[col] }
+        };
+        Point3D::new(row(self.matrix[0]), row(self.matrix[1]), row(self.matrix[2]))
+    }
+
+    // The rotation applying other first and then self
+    pub fn compose(&self, other: &Rotation) -> Rotation {
+        let mut matrix = [[0; 3]; 3];
+        for (i, row) in matrix.iter_mut().enumerate() {
+            for (j, cell) in row.iter_mut().enumerate() {
+                *cell = (0..3).map(|k| self.matrix[i][k] * other.matrix[k][j]).sum();
+            }
+        }
+        Rotation { matrix }
+    }
+
+    // Rotation matrices are orthogonal, so the inverse is the transpose
+    pub fn inverse(&self) -> Rotation {
+        let m = &self.matrix;
+        Rotation { matrix: std::array::from_fn(|i| std::array::from_fn(|j| m[j][i])) }
+    }
+}
+
+impl Default for Rotation {
+    fn default() -> Self {
+        Self::IDENTITY
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use super::Rotation;
+    use crate::point::Point3D;
+
+    #[test]
+    pub fn test_all() {
+        let all = Rotation::all();
+        assert_eq!(24, all.len());
+        assert_eq!(Rotation::IDENTITY, all[0]);
+        let p = Point3D::new(1, 2, 3);
+        let images: HashSet<_> = all.iter().map(|r| r.apply(p)).collect();
+        assert_eq!(24, images.len());
+        let set: HashSet<_> = all.iter().collect();
+        for a in &all {
+            assert_eq!(Rotation::IDENTITY, a.compose(&a.inverse()));
+            assert!(all.iter().all(|b| set.contains(&a.compose(b))));
+        }
+    }
+
+    #[test]
+    pub fn test_apply() {
+        let (a, b) = (Point3D::new(1, 2, 3), Point3D::new(-4, 0, 5));
+        assert_eq!(Point3D::new(-2, 1, 3), Rotation::about_z().apply(a));
+        assert_eq!(Point3D::new(1, -3, 2), Rotation::about_x().apply(a));
+        assert_eq!(Point3D::new(3, 2, -1), Rotation::about_y().apply(a));
+        let r = Rotation::about_x().compose(&Rotation::about_y());
+        assert_eq!(Rotation::about_x().apply(Rotation::about_y().apply(a)), r.apply(a));
+        for r in Rotation::all() {
+            assert_eq!(r.apply(a.cross(&b)), r.apply(a).cross(&r.apply(b)));
+            assert_eq!(a.dot(&b), r.apply(a).dot(&r.apply(b)));
+        }
+    }
+}
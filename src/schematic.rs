@@ -0,0 +1,79 @@
+use std::collections::HashMap;
+
+use super::grid::{Grid, Token, TokenKind};
+
+pub struct Schematic {
+    size: (usize, usize),
+    numbers: Vec<Token>,
+    // (row, col) -> symbol
+    symbols: HashMap<(usize, usize), char>,
+}
+
+impl Schematic {
+    pub fn new(grid: &Grid<char>) -> Self {
+        let mut numbers = Vec::new();
+        let mut symbols = HashMap::new();
+        for token in grid.tokens('.') {
+            match token.kind {
+                TokenKind::Number => numbers.push(token),
+                _ => for col in token.cols.clone() {
+                    symbols.insert((token.row, col), grid[(token.row, col)]);
+                },
+            }
+        }
+        Self { size: grid.size(), numbers, symbols }
+    }
+
+    // Numbers adjacent to at least one symbol. Here and in gear_ratios, digit
+    // runs too long to fit a usize are skipped.
+    pub fn part_numbers(&self) -> Vec<usize> {
+        self.numbers.iter()
+            .filter(|token| {
+                token.adjacent(self.size).iter().any(|p| self.symbols.contains_key(p))
+            })
+            .filter_map(Token::value)
+            .collect()
+    }
+
+    // Products of the two numbers touching each `*` that touches exactly two
+    pub fn gear_ratios(&self) -> Vec<usize> {
+        let mut gears: HashMap<(usize, usize), Vec<usize>> = HashMap::new();
+        for token in self.numbers.iter() {
+            let Some(n) = token.value() else {
+                continue;
+            };
+            for p in token.adjacent(self.size) {
+                if self.symbols.get(&p) == Some(&'*') {
+                    gears.entry(p).or_default().push(n);
+                }
+            }
+        }
+        gears.values()
+            .filter(|ns| ns.len() == 2)
+            .map(|ns| ns[0] * ns[1])
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Schematic;
+    use crate::grid::Grid;
+
+    #[test]
+    pub fn test_numbers_stop_at_row_end() {
+        let schematic = Schematic::new(&Grid::from_lines(&["..12", "34*."]));
+        let mut parts = schematic.part_numbers();
+        parts.sort_unstable();
+        assert_eq!(vec![12, 34], parts);
+        assert_eq!(vec![408], schematic.gear_ratios());
+    }
+
+    #[test]
+    pub fn test_oversized_numbers_skipped() {
+        let huge = "9".repeat(30);
+        let schematic = Schematic::new(&Grid::from_lines(&[format!("{}*2", huge), ".".repeat(32)]));
+        assert_eq!(vec![2], schematic.part_numbers());
+        assert!(schematic.gear_ratios().is_empty());
+    }
+}
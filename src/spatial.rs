@@ -0,0 +1,461 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+
+use super::point::{Point2D, Point3D};
+
+// Integer coordinates, widened to i128 so that differences along an axis
+// can't overflow. Squared distances still can for 64-bit coordinates more than
+// about 2^62 apart.
+pub trait Coordinate: Copy + Ord {
+    fn to_i128(self) -> i128;
+}
+
+macro_rules! coordinate {
+    ($($t:ty),+) => {
+        $(impl Coordinate for $t {
+            fn to_i128(self) -> i128 {
+                self as i128
+            }
+        })+
+    };
+}
+
+coordinate!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize);
+
+// A point that can be stored in a spatial index
+pub trait Spatial: Copy {
+    const DIMS: usize;
+
+    fn axis(&self, i: usize) -> i128;
+
+    fn distance_squared(&self, other: &Self) -> i128 {
+        (0..Self::DIMS).map(|i| (self.axis(i) - other.axis(i)).pow(2)).sum()
+    }
+
+    fn manhattan(&self, other: &Self) -> i128 {
+        (0..Self::DIMS).map(|i| (self.axis(i) - other.axis(i)).abs()).sum()
+    }
+}
+
+impl<T: Coordinate> Spatial for Point2D<T> {
+    const DIMS: usize = 2;
+
+    fn axis(&self, i: usize) -> i128 {
+        match i {
+            0 => self.x.to_i128(),
+            _ => self.y.to_i128(),
+        }
+    }
+}
+
+impl<T: Coordinate> Spatial for Point3D<T> {
+    const DIMS: usize = 3;
+
+    fn axis(&self, i: usize) -> i128 {
+        match i {
+            0 => self.x.to_i128(),
+            1 => self.y.to_i128(),
+            _ => self.z.to_i128(),
+        }
+    }
+}
+
+fn in_bounds<P: Spatial>(p: &P, bounds: impl Fn(usize) -> (i128, i128)) -> bool {
+    (0..P::DIMS).all(|i| {
+        let (min, max) = bounds(i);
+        (min..=max).contains(&p.axis(i))
+    })
+}
+
+// Per-axis bounds of the box reaching radius from target, which holds both
+// the straight-line and Manhattan balls
+fn around<P: Spatial>(target: &P, radius: i128) -> impl Fn(usize) -> (i128, i128) + '_ {
+    move |i| (target.axis(i) - radius, target.axis(i) + radius)
+}
+
+// A candidate for k-nearest, ordered by distance so the heap pops the worst
+struct Candidate {
+    dist: i128,
+    index: usize,
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.dist == other.dist
+    }
+}
+
+impl Eq for Candidate {}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.dist.cmp(&other.dist)
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/*
+ * A balanced k-d tree stored implicitly in a Vec: the median of each range is
+ * its node, splitting on axis depth % DIMS, with the halves either side of it
+ * as the subtrees. Points can't be added after building.
+ */
+pub struct KdTree<P> {
+    points: Vec<P>,
+}
+
+impl<P: Spatial> KdTree<P> {
+    pub fn new(mut points: Vec<P>) -> Self {
+        Self::build(&mut points, 0);
+        Self { points }
+    }
+
+    fn build(points: &mut [P], depth: usize) {
+        if points.len() <= 1 {
+            return;
+        }
+        let axis = depth % P::DIMS;
+        let mid = points.len() / 2;
+        points.select_nth_unstable_by_key(mid, |p| p.axis(axis));
+        let (left, right) = points.split_at_mut(mid);
+        Self::build(left, depth + 1);
+        Self::build(&mut right[1..], depth + 1);
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn nearest(&self, target: &P) -> Option<&P> {
+        self.k_nearest(target, 1).into_iter().next()
+    }
+
+    // The k points closest to target by straight-line distance, nearest first
+    pub fn k_nearest(&self, target: &P, k: usize) -> Vec<&P> {
+        let mut heap = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.search_nearest(0, self.points.len(), 0, target, k, &mut heap);
+        }
+        heap.into_sorted_vec().into_iter().map(|c| &self.points[c.index]).collect()
+    }
+
+    fn search_nearest(
+        &self, lo: usize, hi: usize, depth: usize, target: &P, k: usize,
+        heap: &mut BinaryHeap<Candidate>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let node = &self.points[mid];
+        heap.push(Candidate { dist: node.distance_squared(target), index: mid });
+        if heap.len() > k {
+            heap.pop();
+        }
+        let axis = depth % P::DIMS;
+        let diff = target.axis(axis) - node.axis(axis);
+        let (near, far) = if diff < 0 { ((lo, mid), (mid + 1, hi)) } else { ((mid + 1, hi), (lo, mid)) };
+        self.search_nearest(near.0, near.1, depth + 1, target, k, heap);
+        // The far side can only help if the splitting plane is closer than
+        // the worst candidate so far
+        if heap.len() < k || diff * diff <= heap.peek().unwrap().dist {
+            self.search_nearest(far.0, far.1, depth + 1, target, k, heap);
+        }
+    }
+
+    // Every point within the inclusive box from min to max
+    pub fn within_box(&self, min: &P, max: &P) -> Vec<&P> {
+        let mut found = Vec::new();
+        self.search_box(0, self.points.len(), 0, &|i| (min.axis(i), max.axis(i)), &mut found);
+        found
+    }
+
+    // Every point within a straight-line distance of radius
+    pub fn within_radius(&self, target: &P, radius: i128) -> Vec<&P> {
+        let mut found = Vec::new();
+        self.search_box(0, self.points.len(), 0, &around(target, radius), &mut found);
+        found.retain(|p| p.distance_squared(target) <= radius * radius);
+        found
+    }
+
+    // Every point within a Manhattan distance of radius
+    pub fn within_manhattan(&self, target: &P, radius: i128) -> Vec<&P> {
+        let mut found = Vec::new();
+        self.search_box(0, self.points.len(), 0, &around(target, radius), &mut found);
+        found.retain(|p| p.manhattan(target) <= radius);
+        found
+    }
+
+    // Collects points whose every coordinate lies within the (min, max) bounds
+    // given for its axis
+    fn search_box<'a, B>(&'a self, lo: usize, hi: usize, depth: usize, bounds: &B, found: &mut Vec<&'a P>)
+    where
+        B: Fn(usize) -> (i128, i128),
+    {
+        if lo >= hi {
+            return;
+        }
+        let mid = lo + (hi - lo) / 2;
+        let node = &self.points[mid];
+        if in_bounds(node, bounds) {
+            found.push(node);
+        }
+        let axis = depth % P::DIMS;
+        let (min, max) = bounds(axis);
+        if min <= node.axis(axis) {
+            self.search_box(lo, mid, depth + 1, bounds, found);
+        }
+        if max >= node.axis(axis) {
+            self.search_box(mid + 1, hi, depth + 1, bounds, found);
+        }
+    }
+}
+
+/*
+ * Buckets points into cubic cells of a fixed size. Unlike the k-d tree it
+ * accepts new points at any time, and queries only visit the cells they
+ * overlap, or every occupied cell if there are fewer of those. It suits dense
+ * points and radii close to the cell size.
+ */
+pub struct SpatialHash<P> {
+    cell_size: i128,
+    cells: HashMap<[i128; 3], Vec<P>>,
+    len: usize,
+    // Inclusive corners of the occupied cells
+    min_cell: [i128; 3],
+    max_cell: [i128; 3],
+}
+
+impl<P: Spatial> SpatialHash<P> {
+    pub fn new(cell_size: i128) -> Self {
+        assert!(cell_size > 0, "cell size must be positive");
+        Self { cell_size, cells: HashMap::new(), len: 0, min_cell: [0; 3], max_cell: [0; 3] }
+    }
+
+    fn cell_of(&self, p: &P) -> [i128; 3] {
+        std::array::from_fn(|i| if i < P::DIMS { p.axis(i).div_euclid(self.cell_size) } else { 0 })
+    }
+
+    pub fn insert(&mut self, p: P) {
+        let cell = self.cell_of(&p);
+        if self.is_empty() {
+            (self.min_cell, self.max_cell) = (cell, cell);
+        }
+        self.min_cell = std::array::from_fn(|i| self.min_cell[i].min(cell[i]));
+        self.max_cell = std::array::from_fn(|i| self.max_cell[i].max(cell[i]));
+        self.cells.entry(cell).or_default().push(p);
+        self.len += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // Points in every cell overlapping the given per-axis bounds, or in every
+    // occupied cell when that would mean looking up fewer cells
+    fn candidates(&self, bounds: impl Fn(usize) -> (i128, i128)) -> Vec<&P> {
+        let range = |i: usize| {
+            let (min, max) = bounds(i);
+            (min.div_euclid(self.cell_size), max.div_euclid(self.cell_size))
+        };
+        let lo: [i128; 3] = std::array::from_fn(|i| if i < P::DIMS { range(i).0 } else { 0 });
+        let hi: [i128; 3] = std::array::from_fn(|i| if i < P::DIMS { range(i).1 } else { 0 });
+        let count = (0..3).fold(1i128, |n, i| n.saturating_mul(hi[i] - lo[i] + 1));
+        if count > self.cells.len() as i128 {
+            return self.cells.values().flatten().collect();
+        }
+        cells_between(lo, hi).filter_map(|cell| self.cells.get(&cell)).flatten().collect()
+    }
+
+    // Every point within the inclusive box from min to max
+    pub fn within_box(&self, min: &P, max: &P) -> Vec<&P> {
+        let bounds = |i| (min.axis(i), max.axis(i));
+        self.candidates(bounds).into_iter().filter(|p| in_bounds(*p, bounds)).collect()
+    }
+
+    // Every point within a straight-line distance of radius
+    pub fn within_radius(&self, target: &P, radius: i128) -> Vec<&P> {
+        self.candidates(around(target, radius)).into_iter()
+            .filter(|p| p.distance_squared(target) <= radius * radius)
+            .collect()
+    }
+
+    // Every point within a Manhattan distance of radius
+    pub fn within_manhattan(&self, target: &P, radius: i128) -> Vec<&P> {
+        self.candidates(around(target, radius)).into_iter()
+            .filter(|p| p.manhattan(target) <= radius)
+            .collect()
+    }
+
+    /*
+     * Searches rings of cells outwards from the target's cell. Any point
+     * beyond ring r is more than r cells away along some axis, so once the
+     * best so far is within r * cell_size nothing further out can beat it.
+     * Rings far from every point would hold more cells than are occupied, so
+     * from then on it is cheaper to check every occupied cell directly.
+     */
+    pub fn nearest(&self, target: &P) -> Option<&P> {
+        if self.is_empty() {
+            return None;
+        }
+        let centre = self.cell_of(target);
+        let reach = (0..P::DIMS)
+            .map(|i| (centre[i] - self.min_cell[i]).abs().max((self.max_cell[i] - centre[i]).abs()))
+            .max()
+            .unwrap();
+        let mut best = None;
+        for r in 0..=reach {
+            // Each face of ring r alone has (2r + 1)^(DIMS - 1) cells
+            let face = (2 * r + 1).saturating_pow(P::DIMS as u32 - 1);
+            if face > self.cells.len() as i128 {
+                best = closest(best, self.cells.values().flatten(), target);
+                break;
+            }
+            let ring = shell(P::DIMS, r).into_iter()
+                .map(|offset| std::array::from_fn(|i| centre[i] + offset[i]));
+            best = closest(best, ring.filter_map(|cell| self.cells.get(&cell)).flatten(), target);
+            let done = best.is_some_and(|(d, _)| {
+                (r * self.cell_size).checked_pow(2).is_none_or(|bound| d <= bound)
+            });
+            if done {
+                break;
+            }
+        }
+        best.map(|(_, p)| p)
+    }
+}
+
+// The better of best and the closest of points to target, with its squared
+// distance
+fn closest<'a, P: Spatial>(
+    best: Option<(i128, &'a P)>, points: impl Iterator<Item = &'a P>, target: &P,
+) -> Option<(i128, &'a P)> {
+    points.fold(best, |best, p| {
+        let dist = p.distance_squared(target);
+        if best.is_none_or(|(d, _)| dist < d) { Some((dist, p)) } else { best }
+    })
+}
+
+// Offsets of the cells exactly r cells away along the furthest of the first
+// dims axes, without visiting the cells inside
+fn shell(dims: usize, r: i128) -> Vec<[i128; 3]> {
+    fn fill(axis: usize, dims: usize, r: i128, on_shell: bool, cell: &mut [i128; 3], out: &mut Vec<[i128; 3]>) {
+        if axis == dims {
+            out.push(*cell);
+            return;
+        }
+        if axis == dims - 1 && !on_shell {
+            // Nothing earlier reached the shell, so this axis has to
+            for v in if r == 0 { vec![0] } else { vec![-r, r] } {
+                cell[axis] = v;
+                fill(axis + 1, dims, r, true, cell, out);
+            }
+            return;
+        }
+        for v in -r..=r {
+            cell[axis] = v;
+            fill(axis + 1, dims, r, on_shell || v.abs() == r, cell, out);
+        }
+    }
+    let mut out = Vec::new();
+    fill(0, dims, r, false, &mut [0; 3], &mut out);
+    out
+}
+
+// Every cell from lo to hi inclusive
+fn cells_between(lo: [i128; 3], hi: [i128; 3]) -> impl Iterator<Item = [i128; 3]> {
+    (lo[0]..=hi[0]).flat_map(move |x| {
+        (lo[1]..=hi[1]).flat_map(move |y| (lo[2]..=hi[2]).map(move |z| [x, y, z]))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{KdTree, SpatialHash};
+    use crate::point::{Point2D, Point3D};
+
+    fn scatter(n: usize) -> Vec<Point3D<i64>> {
+        let mut seed = 12345u64;
+        let mut next = move || {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            ((seed >> 33) % 201) as i64 - 100
+        };
+        (0..n).map(|_| Point3D::new(next(), next(), next())).collect()
+    }
+
+    fn sorted(mut points: Vec<&Point3D<i64>>) -> Vec<Point3D<i64>> {
+        points.sort_by_key(|p| (p.x, p.y, p.z));
+        points.into_iter().copied().collect()
+    }
+
+    #[test]
+    pub fn test_matches_brute_force() {
+        let points = scatter(500);
+        let tree = KdTree::new(points.clone());
+        let mut hash = SpatialHash::new(16);
+        points.iter().for_each(|&p| hash.insert(p));
+        assert_eq!(500, tree.len());
+        assert_eq!(500, hash.len());
+        for target in scatter(20).iter().map(|p| *p * 2) {
+            let mut by_dist = points.clone();
+            by_dist.sort_by_key(|p| p.distance_squared(&target));
+            let best = by_dist[0].distance_squared(&target);
+            assert_eq!(best, tree.nearest(&target).unwrap().distance_squared(&target));
+            assert_eq!(best, hash.nearest(&target).unwrap().distance_squared(&target));
+            let k: Vec<_> = tree.k_nearest(&target, 5).iter().map(|p| p.distance_squared(&target)).collect();
+            let expected: Vec<_> = by_dist[..5].iter().map(|p| p.distance_squared(&target)).collect();
+            assert_eq!(expected, k);
+
+            let near = sorted(points.iter().filter(|p| p.distance_squared(&target) <= 900).collect());
+            assert_eq!(near, sorted(tree.within_radius(&target, 30)));
+            assert_eq!(near, sorted(hash.within_radius(&target, 30)));
+            let near = sorted(points.iter().filter(|p| p.manhattan(&target) <= 40).collect());
+            assert_eq!(near, sorted(tree.within_manhattan(&target, 40)));
+            assert_eq!(near, sorted(hash.within_manhattan(&target, 40)));
+        }
+    }
+
+    #[test]
+    pub fn test_box() {
+        let points: Vec<Point2D<u8>> = (0..10).flat_map(|x| (0..10).map(move |y| Point2D::new(x, y))).collect();
+        let tree = KdTree::new(points.clone());
+        let mut hash = SpatialHash::new(3);
+        points.iter().for_each(|&p| hash.insert(p));
+        let (min, max) = (Point2D::new(2, 7), Point2D::new(4, 9));
+        assert_eq!(9, tree.within_box(&min, &max).len());
+        assert_eq!(9, hash.within_box(&min, &max).len());
+        assert_eq!(Some(&Point2D::new(9, 9)), tree.nearest(&Point2D::new(200, 200)));
+        assert_eq!(Some(&Point2D::new(9, 9)), hash.nearest(&Point2D::new(200, 200)));
+        assert!(KdTree::<Point2D<u8>>::new(vec![]).nearest(&min).is_none());
+        assert!(SpatialHash::<Point2D<u8>>::new(1).nearest(&min).is_none());
+    }
+
+    #[test]
+    pub fn test_far_target() {
+        let points: Vec<Point3D<i64>> = (0..100).map(|i| Point3D::new(i * 10, i * 10, i * 10)).collect();
+        let tree = KdTree::new(points.clone());
+        let mut hash = SpatialHash::new(10);
+        points.iter().for_each(|&p| hash.insert(p));
+        for target in [Point3D::new(4000, 3990, 4010), Point3D::new(-3000, 0, 500), Point3D::new(1_000_000_000, 0, 0)] {
+            let expected = points.iter().min_by_key(|p| p.distance_squared(&target));
+            assert_eq!(expected, tree.nearest(&target));
+            assert_eq!(expected, hash.nearest(&target));
+        }
+        let mut flat = SpatialHash::new(10);
+        (0..100).for_each(|i| flat.insert(Point2D::new(i * 10, i * 10)));
+        assert_eq!(Some(&Point2D::new(990, 990)), flat.nearest(&Point2D::new(4000, 3000)));
+        assert_eq!(100, flat.within_manhattan(&Point2D::new(0, 0), 1 << 60).len());
+    }
+}
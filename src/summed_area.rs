@@ -0,0 +1,152 @@
+use std::ops::{Add, Neg, Range, Sub};
+
+use super::grid::Grid;
+
+/*
+ * Each entry (row, col) holds the sum of every cell above and to the left of
+ * it, exclusive, so the table is one larger than the source in both
+ * dimensions and any rectangle sums with four lookups.
+ */
+pub struct SummedArea<T> {
+    table: Grid<T>,
+}
+
+impl<T> SummedArea<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T>,
+{
+    pub fn new(grid: &Grid<T>) -> Self {
+        let (rows, cols) = grid.size();
+        let mut table: Grid<T> = Grid::new(rows + 1, cols + 1);
+        for (row, col, &cell) in grid.iter() {
+            table[(row + 1, col + 1)] =
+                cell + table[(row, col + 1)] + table[(row + 1, col)] - table[(row, col)];
+        }
+        Self { table }
+    }
+
+    // Size of the source grid
+    pub fn size(&self) -> (usize, usize) {
+        let (rows, cols) = self.table.size();
+        (rows - 1, cols - 1)
+    }
+
+    // Sum of the cells within the given row and column ranges
+    pub fn sum(&self, rows: Range<usize>, cols: Range<usize>) -> T {
+        if rows.is_empty() || cols.is_empty() {
+            return T::default();
+        }
+        let t = &self.table;
+        t[(rows.end, cols.end)] + t[(rows.start, cols.start)]
+            - t[(rows.start, cols.end)] - t[(rows.end, cols.start)]
+    }
+
+    pub fn total(&self) -> T {
+        let (rows, cols) = self.size();
+        self.sum(0..rows, 0..cols)
+    }
+}
+
+// Running totals along each row, so (row, col) sums columns 0 to col
+pub fn row_prefix_sums<T>(grid: &Grid<T>) -> Grid<T>
+where
+    T: Copy + Add<Output = T>,
+{
+    let mut sums = grid.clone();
+    let (rows, cols) = sums.size();
+    for row in 0..rows {
+        for col in 1..cols {
+            sums[(row, col)] = sums[(row, col)] + sums[(row, col - 1)];
+        }
+    }
+    sums
+}
+
+// Running totals down each column, so (row, col) sums rows 0 to row
+pub fn col_prefix_sums<T>(grid: &Grid<T>) -> Grid<T>
+where
+    T: Copy + Add<Output = T>,
+{
+    let mut sums = grid.clone();
+    let (rows, cols) = sums.size();
+    for row in 1..rows {
+        for col in 0..cols {
+            sums[(row, col)] = sums[(row, col)] + sums[(row - 1, col)];
+        }
+    }
+    sums
+}
+
+/*
+ * Records bulk rectangle updates in O(1) each by marking only the corners,
+ * then resolves them all at once with a 2D prefix sum. The corner marks go
+ * negative, so T has to be signed, which the Neg bound enforces.
+ */
+pub struct DifferenceGrid<T> {
+    diffs: Grid<T>,
+}
+
+impl<T> DifferenceGrid<T>
+where
+    T: Copy + Default + Add<Output = T> + Sub<Output = T> + Neg<Output = T>,
+{
+    pub fn new(rows: usize, cols: usize) -> Self {
+        Self { diffs: Grid::new(rows + 1, cols + 1) }
+    }
+
+    // Adds value to every cell within the given row and column ranges
+    pub fn add(&mut self, rows: Range<usize>, cols: Range<usize>, value: T) {
+        if rows.is_empty() || cols.is_empty() {
+            return;
+        }
+        let d = &mut self.diffs;
+        d[(rows.start, cols.start)] = d[(rows.start, cols.start)] + value;
+        d[(rows.start, cols.end)] = d[(rows.start, cols.end)] - value;
+        d[(rows.end, cols.start)] = d[(rows.end, cols.start)] - value;
+        d[(rows.end, cols.end)] = d[(rows.end, cols.end)] + value;
+    }
+
+    pub fn build(&self) -> Grid<T> {
+        let (rows, cols) = self.diffs.size();
+        let mut sums = col_prefix_sums(&row_prefix_sums(&self.diffs));
+        sums.crop(0..rows - 1, 0..cols - 1);
+        sums
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{col_prefix_sums, row_prefix_sums, DifferenceGrid, SummedArea};
+    use crate::grid::Grid;
+
+    #[test]
+    pub fn test_rectangle_sums() {
+        let grid = Grid::new_with_cells((1..=12).collect::<Vec<i64>>(), 3, 4);
+        let table = SummedArea::new(&grid);
+        assert_eq!(78, table.total());
+        assert_eq!(6 + 7 + 10 + 11, table.sum(1..3, 1..3));
+        assert_eq!(0, table.sum(2..2, 0..4));
+        assert_eq!(&[1, 3, 6, 10], &row_prefix_sums(&grid)[0]);
+        assert_eq!(&[15, 18, 21, 24], &col_prefix_sums(&grid)[2]);
+    }
+
+    #[test]
+    pub fn test_unsigned_sums() {
+        // As read by read_uint_grid, where no intermediate may go negative
+        let grid = Grid::new_with_cells((1..=12).collect::<Vec<usize>>(), 3, 4);
+        let table = SummedArea::new(&grid);
+        assert_eq!(78, table.total());
+        assert_eq!(6 + 7 + 10 + 11, table.sum(1..3, 1..3));
+        assert_eq!(12, table.sum(2..3, 3..4));
+        assert_eq!(1 + 2 + 5 + 6, table.sum(0..2, 0..2));
+    }
+
+    #[test]
+    pub fn test_difference_grid() {
+        let mut diffs: DifferenceGrid<i32> = DifferenceGrid::new(3, 3);
+        diffs.add(0..2, 0..2, 1);
+        diffs.add(1..3, 1..3, 2);
+        let expected = Grid::new_with_cells(vec![1, 1, 0, 1, 3, 2, 0, 2, 2], 3, 3);
+        assert_eq!(expected, diffs.build());
+    }
+}